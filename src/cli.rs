@@ -0,0 +1,163 @@
+use color_eyre::{eyre::eyre, Result};
+use std::path::PathBuf;
+use typirst::export::ExportFormat;
+
+pub const USAGE: &str = "\
+Usage: typirst [COMMAND]
+       typirst [--file <FILE> | -] [--offset <N>] [--length <N>]
+
+Commands:
+  (none)    Start a typing test
+  export    Write results from the history to JSON, CSV or summary cards
+  stats     Print a summary of the history
+  help      Print this help
+
+Text options:
+  --file <FILE>         Type the text of FILE instead of generated text
+  -                     Type the text read from stdin
+  --offset <N>          Start N characters into the text, instead of where the
+                        last session stopped
+  --length <N>          Type the text N characters at a time
+
+Export options:
+  --format <json|csv|card>
+                        Output format (default: json)
+  --keystrokes          With csv, write one row per keystroke instead of per test
+  --last                Only export the most recent result
+  --output <FILE>       Write to FILE instead of stdout
+
+Stats options:
+  --last <N>            Number of recent tests the accuracy trend covers (default: 10)
+";
+
+#[derive(Debug)]
+pub enum Command {
+    Run(RunArgs),
+    Export(ExportArgs),
+    Stats { trend_len: usize },
+    Help,
+}
+
+#[derive(Debug, Default)]
+pub struct RunArgs {
+    pub text: Option<TextSource>,
+    pub offset: Option<usize>,
+    pub length: Option<usize>,
+}
+
+#[derive(Debug)]
+pub enum TextSource {
+    Stdin,
+    File(PathBuf),
+}
+
+#[derive(Debug)]
+pub struct ExportArgs {
+    pub format: ExportFormat,
+    pub last: bool,
+    pub output: Option<PathBuf>,
+}
+
+pub fn parse_args(mut args: impl Iterator<Item = String>) -> Result<Command> {
+    match args.next().as_deref() {
+        None => Ok(Command::Run(RunArgs::default())),
+        Some("help" | "--help" | "-h") => Ok(Command::Help),
+        Some("export") => parse_export_args(args).map(Command::Export),
+        Some("stats") => parse_stats_args(args),
+        Some(first) if first == "-" || first.starts_with("--") => {
+            parse_run_args(std::iter::once(first.to_string()).chain(args)).map(Command::Run)
+        }
+        Some(other) => Err(eyre!("Unknown command '{}'", other)),
+    }
+}
+
+fn parse_run_args(mut args: impl Iterator<Item = String>) -> Result<RunArgs> {
+    let mut run_args = RunArgs::default();
+
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "-" => run_args.text = Some(TextSource::Stdin),
+            "--file" => {
+                run_args.text = Some(TextSource::File(PathBuf::from(
+                    args.next().ok_or_else(|| eyre!("--file needs a file"))?,
+                )))
+            }
+            "--offset" => {
+                run_args.offset = Some(
+                    args.next()
+                        .and_then(|n| n.parse().ok())
+                        .ok_or_else(|| eyre!("--offset needs a number"))?,
+                )
+            }
+            "--length" => {
+                run_args.length = Some(
+                    args.next()
+                        .and_then(|n| n.parse().ok())
+                        .filter(|n| *n > 0)
+                        .ok_or_else(|| eyre!("--length needs a positive number"))?,
+                )
+            }
+            other => return Err(eyre!("Unknown option '{}'", other)),
+        }
+    }
+
+    if run_args.text.is_none() && (run_args.offset.is_some() || run_args.length.is_some()) {
+        return Err(eyre!("--offset and --length need --file or -"));
+    }
+    Ok(run_args)
+}
+
+fn parse_export_args(mut args: impl Iterator<Item = String>) -> Result<ExportArgs> {
+    let mut format = ExportFormat::Json;
+    let mut keystrokes = false;
+    let mut last = false;
+    let mut output = None;
+
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--format" => match args.next().as_deref() {
+                Some("json") => format = ExportFormat::Json,
+                Some("csv") => format = ExportFormat::Csv,
+                Some("card") => format = ExportFormat::Card,
+                other => return Err(eyre!("Invalid export format {:?}", other)),
+            },
+            "--keystrokes" => keystrokes = true,
+            "--last" => last = true,
+            "--output" => {
+                output = Some(PathBuf::from(
+                    args.next().ok_or_else(|| eyre!("--output needs a file"))?,
+                ))
+            }
+            other => return Err(eyre!("Unknown export option '{}'", other)),
+        }
+    }
+
+    if keystrokes && format == ExportFormat::Csv {
+        format = ExportFormat::KeystrokesCsv;
+    }
+
+    Ok(ExportArgs {
+        format,
+        last,
+        output,
+    })
+}
+
+fn parse_stats_args(mut args: impl Iterator<Item = String>) -> Result<Command> {
+    let mut trend_len = 10;
+
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--last" => {
+                trend_len = args
+                    .next()
+                    .and_then(|n| n.parse().ok())
+                    .filter(|n| *n > 0)
+                    .ok_or_else(|| eyre!("--last needs a positive number"))?
+            }
+            other => return Err(eyre!("Unknown stats option '{}'", other)),
+        }
+    }
+
+    Ok(Command::Stats { trend_len })
+}
@@ -0,0 +1,98 @@
+use crate::keymap::Action;
+use crate::layout::KeyboardLayout;
+use crate::options::{
+    Caret, DailyGoal, Frequency, Highlight, LiveStats, NumberOfWords, OnScreenKeyboard,
+    ResultsView, Scrolling, TextMode, VisibleLines,
+};
+use color_eyre::{eyre::WrapErr, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs::{self, create_dir_all};
+use std::path::PathBuf;
+
+const CONFIG_FILE: &str = "config.json";
+
+/// The directory where typirst keeps its log, configuration and other data
+pub fn data_dir() -> PathBuf {
+    let mut path = dirs::data_dir().unwrap_or_else(|| PathBuf::from("."));
+    path.push("typirst");
+    path
+}
+
+/// The options chosen in the pause menu, persisted between runs
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Config {
+    pub number_of_words: NumberOfWords,
+    pub text_mode: TextMode,
+    pub capitals: Frequency,
+    pub numbers: Frequency,
+    pub punctuation: Frequency,
+    pub brackets: Frequency,
+    pub highlight: Highlight,
+    pub visible_lines: VisibleLines,
+    pub scrolling: Scrolling,
+    pub caret: Caret,
+    pub keyboard: OnScreenKeyboard,
+    pub live_stats: LiveStats,
+    pub results_view: ResultsView,
+    pub daily_goal: DailyGoal,
+    /// Letter combinations drilled in the n-gram modes, e.g. `["th", "ing"]`.
+    /// If empty, the slowest letter pairs in the history are drilled.
+    pub ngrams: Vec<String>,
+    /// The keyboard layout used for learning and per-finger statistics
+    pub layout: KeyboardLayout,
+    /// Pauses the test after this many seconds without typing, leaving the
+    /// idle time out of the result. 0 never pauses.
+    pub idle_pause_secs: u64,
+    /// Key bindings that differ from the defaults, e.g. `"pause": "ctrl-p"`
+    pub keys: BTreeMap<Action, String>,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Self {
+            number_of_words: NumberOfWords::Ten,
+            text_mode: TextMode::Words,
+            capitals: Frequency::OFF,
+            numbers: Frequency::OFF,
+            punctuation: Frequency::OFF,
+            brackets: Frequency::OFF,
+            highlight: Highlight::Nothing,
+            visible_lines: VisibleLines::Five,
+            scrolling: Scrolling::Middle,
+            caret: Caret::Bar,
+            keyboard: OnScreenKeyboard::Hidden,
+            live_stats: LiveStats::Hidden,
+            results_view: ResultsView::Chart,
+            daily_goal: DailyGoal::Off,
+            ngrams: vec![],
+            layout: KeyboardLayout::Qwerty,
+            idle_pause_secs: 10,
+            keys: BTreeMap::new(),
+        }
+    }
+}
+
+impl Config {
+    /// Loads the configuration from the data directory, falling back to the
+    /// defaults if there is none or it cannot be parsed.
+    pub fn load() -> Self {
+        let path = data_dir().join(CONFIG_FILE);
+        let Ok(contents) = fs::read_to_string(&path) else {
+            return Self::default();
+        };
+
+        serde_json::from_str(&contents).unwrap_or_else(|err| {
+            log::warn!("Ignoring invalid config {}: {}", path.display(), err);
+            Self::default()
+        })
+    }
+
+    pub fn save(&self) -> Result<()> {
+        let dir = data_dir();
+        create_dir_all(&dir).wrap_err("Creating data directory failed.")?;
+        let contents = serde_json::to_string_pretty(self)?;
+        fs::write(dir.join(CONFIG_FILE), contents).wrap_err("Writing config failed.")
+    }
+}
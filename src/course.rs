@@ -0,0 +1,222 @@
+use crate::config::data_dir;
+use crate::history::TestResult;
+use crate::options::{Frequency, TextModifiers};
+use color_eyre::{
+    eyre::{eyre, WrapErr},
+    Result,
+};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet};
+use std::fs::{self, create_dir_all};
+
+/// A custom course in the data directory replaces the built-in one
+const COURSE_FILE: &str = "course.json";
+const PROGRESS_FILE: &str = "course_progress.json";
+
+/// An ordered list of lessons, each unlocked by passing the previous one
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Course {
+    pub name: String,
+    pub lessons: Vec<Lesson>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Lesson {
+    pub name: String,
+    /// The only characters the words may contain. Made up words are used if
+    /// the word list has too few words with just these keys.
+    #[serde(default)]
+    pub keys: Option<String>,
+    /// The words to practise, instead of the word list
+    #[serde(default)]
+    pub words: Option<Vec<String>>,
+    #[serde(default = "off")]
+    pub capitals: Frequency,
+    #[serde(default = "off")]
+    pub numbers: Frequency,
+    #[serde(default = "off")]
+    pub punctuation: Frequency,
+    #[serde(default = "off")]
+    pub brackets: Frequency,
+    pub target_wpm: f64,
+    pub target_accuracy: f64,
+}
+
+fn off() -> Frequency {
+    Frequency::OFF
+}
+
+impl Lesson {
+    pub fn modifiers(&self) -> TextModifiers {
+        TextModifiers {
+            capitals: self.capitals,
+            numbers: self.numbers,
+            punctuation: self.punctuation,
+            brackets: self.brackets,
+        }
+    }
+
+    pub fn passed_by(&self, result: &TestResult) -> bool {
+        result.wpm >= self.target_wpm && result.accuracy >= self.target_accuracy
+    }
+
+    /// Checks the settings the text is generated from: the frequencies are
+    /// percentages and the keys, if given, must leave something to type
+    fn validate(&self) -> Result<()> {
+        let frequencies = [
+            ("capitals", self.capitals),
+            ("numbers", self.numbers),
+            ("punctuation", self.punctuation),
+            ("brackets", self.brackets),
+        ];
+        for (modifier, frequency) in frequencies {
+            if frequency.0 > 100 {
+                return Err(eyre!(
+                    "lesson '{}' has {} at {}%, more than 100%",
+                    self.name,
+                    modifier,
+                    frequency.0
+                ));
+            }
+        }
+        if self
+            .keys
+            .as_ref()
+            .is_some_and(|keys| keys.trim().is_empty())
+        {
+            return Err(eyre!("lesson '{}' has no keys", self.name));
+        }
+        Ok(())
+    }
+}
+
+impl Course {
+    /// Loads the course from the data directory, or the built-in beginner
+    /// course if there is none or it cannot be parsed
+    pub fn load() -> Self {
+        let path = data_dir().join(COURSE_FILE);
+        if let Ok(contents) = fs::read_to_string(&path) {
+            match Self::parse(&contents) {
+                Ok(course) => return course,
+                Err(err) => log::warn!("Ignoring invalid course {}: {}", path.display(), err),
+            }
+        }
+        Self::default()
+    }
+
+    fn parse(contents: &str) -> Result<Self> {
+        let course: Course = serde_json::from_str(contents)?;
+        if course.lessons.is_empty() {
+            return Err(eyre!("the course has no lessons"));
+        }
+        for lesson in &course.lessons {
+            lesson.validate()?;
+        }
+        Ok(course)
+    }
+}
+
+impl Default for Course {
+    fn default() -> Self {
+        serde_json::from_str(include_str!("../assets/course.json"))
+            .expect("the built-in course is valid")
+    }
+}
+
+/// The lessons passed in each course, by course name
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct CourseProgress {
+    passed: BTreeMap<String, BTreeSet<usize>>,
+}
+
+impl CourseProgress {
+    /// Loads the progress from the data directory, starting over if there is
+    /// none or it cannot be parsed
+    pub fn load() -> Self {
+        let path = data_dir().join(PROGRESS_FILE);
+        let Ok(contents) = fs::read_to_string(&path) else {
+            return Self::default();
+        };
+
+        serde_json::from_str(&contents).unwrap_or_else(|err| {
+            log::warn!(
+                "Ignoring invalid course progress {}: {}",
+                path.display(),
+                err
+            );
+            Self::default()
+        })
+    }
+
+    pub fn save(&self) -> Result<()> {
+        let dir = data_dir();
+        create_dir_all(&dir).wrap_err("Creating data directory failed.")?;
+        let contents = serde_json::to_string_pretty(self)?;
+        fs::write(dir.join(PROGRESS_FILE), contents).wrap_err("Writing course progress failed.")
+    }
+
+    pub fn is_passed(&self, course: &Course, lesson: usize) -> bool {
+        self.passed
+            .get(&course.name)
+            .is_some_and(|passed| passed.contains(&lesson))
+    }
+
+    /// Lessons are unlocked once the previous one is passed
+    pub fn is_unlocked(&self, course: &Course, lesson: usize) -> bool {
+        lesson == 0 || self.is_passed(course, lesson - 1)
+    }
+
+    pub fn pass(&mut self, course: &Course, lesson: usize) {
+        self.passed
+            .entry(course.name.clone())
+            .or_default()
+            .insert(lesson);
+    }
+
+    /// The first lesson that has not been passed yet, or the last one if all
+    /// of them have
+    pub fn next_lesson(&self, course: &Course) -> usize {
+        (0..course.lessons.len())
+            .find(|lesson| !self.is_passed(course, *lesson))
+            .unwrap_or(course.lessons.len() - 1)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn lessons_unlock_in_order() {
+        let course = Course::default();
+        let mut progress = CourseProgress::default();
+        assert!(progress.is_unlocked(&course, 0));
+        assert!(!progress.is_unlocked(&course, 1));
+        assert_eq!(progress.next_lesson(&course), 0);
+
+        progress.pass(&course, 0);
+        assert!(progress.is_unlocked(&course, 1));
+        assert_eq!(progress.next_lesson(&course), 1);
+    }
+
+    #[test]
+    fn courses_with_unusable_lessons_are_rejected() {
+        let course = |lesson: &str| {
+            format!(
+                r#"{{"name":"Test","lessons":[{{"name":"One","target_wpm":10,"target_accuracy":90{}}}]}}"#,
+                lesson
+            )
+        };
+        assert!(Course::parse(&course("")).is_ok());
+        assert!(Course::parse(&course(r#","capitals":100"#)).is_ok());
+        let err = Course::parse(&course(r#","capitals":150"#)).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "lesson 'One' has capitals at 150%, more than 100%"
+        );
+        let err = Course::parse(&course(r#","keys":"""#)).unwrap_err();
+        assert_eq!(err.to_string(), "lesson 'One' has no keys");
+        assert!(Course::parse(r#"{"name":"Test","lessons":[]}"#).is_err());
+        assert!(Course::parse(include_str!("../assets/course.json")).is_ok());
+    }
+}
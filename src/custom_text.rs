@@ -0,0 +1,241 @@
+use crate::config::data_dir;
+use crate::utils::prepare_text;
+use color_eyre::{
+    eyre::{eyre, WrapErr},
+    Result,
+};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs::{self, create_dir_all};
+use std::io;
+use std::path::Path;
+
+const POSITIONS_FILE: &str = "text_positions.json";
+
+/// A text given on the command line, typed a part at a time. The position
+/// reached in a file is saved so that a later session can resume from there.
+#[derive(Debug, Clone)]
+pub struct CustomText {
+    /// The file name, shown with the progress through the text
+    pub name: String,
+    /// Identifies the file among the saved positions, none for stdin
+    key: Option<String>,
+    chars: Vec<char>,
+    /// The number of characters in each part, the whole text if none
+    length: Option<usize>,
+    /// Whether to offer resuming, which an explicit offset overrides
+    resumable: bool,
+    /// Where the part being typed starts
+    start: usize,
+    /// Where the next part starts
+    position: usize,
+}
+
+impl CustomText {
+    fn new(
+        name: String,
+        key: Option<String>,
+        text: &str,
+        offset: Option<usize>,
+        length: Option<usize>,
+    ) -> Result<Self> {
+        let chars: Vec<char> = prepare_text(text).chars().collect();
+        if chars.is_empty() {
+            return Err(eyre!("There is no text to type."));
+        }
+
+        let mut text = Self {
+            name,
+            key,
+            chars,
+            length,
+            resumable: offset.is_none(),
+            start: 0,
+            position: 0,
+        };
+        text.resume(offset.unwrap_or(0));
+        Ok(text)
+    }
+
+    pub fn from_stdin(offset: Option<usize>, length: Option<usize>) -> Result<Self> {
+        let text = io::read_to_string(io::stdin()).wrap_err("Reading stdin failed.")?;
+        Self::new("stdin".to_string(), None, &text, offset, length)
+    }
+
+    pub fn from_file(path: &Path, offset: Option<usize>, length: Option<usize>) -> Result<Self> {
+        let text = fs::read_to_string(path)
+            .wrap_err_with(|| format!("Reading {} failed.", path.display()))?;
+        let name = path.file_name().map_or(path.display().to_string(), |name| {
+            name.to_string_lossy().to_string()
+        });
+        let key = fs::canonicalize(path)
+            .ok()
+            .map(|path| path.display().to_string());
+        Self::new(name, key, &text, offset, length)
+    }
+
+    /// The part of the text to type
+    pub fn part(&self) -> String {
+        self.chars[self.start..self.part_end()].iter().collect()
+    }
+
+    /// Where the part being typed ends, extended to the end of a word
+    fn part_end(&self) -> usize {
+        let Some(length) = self.length else {
+            return self.chars.len();
+        };
+        let mut end = (self.start + length).min(self.chars.len());
+        while end < self.chars.len() && !self.chars[end].is_whitespace() {
+            end += 1;
+        }
+        end
+    }
+
+    /// Moves the position to the start of the word at `position`, skipping
+    /// whitespace, and back to the beginning after the end of the text
+    fn move_to(&mut self, position: usize) {
+        let len = self.chars.len();
+        let mut position = position.min(len);
+        while position > 0
+            && position < len
+            && !self.chars[position].is_whitespace()
+            && !self.chars[position - 1].is_whitespace()
+        {
+            position -= 1;
+        }
+        while position < len && self.chars[position].is_whitespace() {
+            position += 1;
+        }
+        self.position = if position == len { 0 } else { position };
+    }
+
+    /// Starts typing from the given position
+    pub fn resume(&mut self, position: usize) {
+        self.move_to(position);
+        self.start = self.position;
+    }
+
+    /// Starts the next part where the previous one stopped
+    pub fn next_part(&mut self) {
+        self.start = self.position;
+    }
+
+    /// Moves past the finished part, returning whether that was the end of the
+    /// text, in which case it starts over
+    pub fn finish_part(&mut self) -> bool {
+        self.move_to(self.part_end());
+        self.position == 0
+    }
+
+    /// Records that typing stopped `typed` characters into the part
+    pub fn stop_at(&mut self, typed: usize) {
+        self.move_to(self.start + typed);
+    }
+
+    /// How much of the text comes before the part being typed, in percent
+    pub fn progress(&self) -> f64 {
+        self.percent(self.start)
+    }
+
+    /// How much of the text comes before the position, in percent
+    pub fn percent(&self, position: usize) -> f64 {
+        position as f64 / self.chars.len() as f64 * 100.0
+    }
+
+    /// The position reached in the file in an earlier session, if there is
+    /// one to resume from
+    pub fn saved_position(&self) -> Option<usize> {
+        let key = self.key.as_ref().filter(|_| self.resumable)?;
+        TextPositions::load()
+            .positions
+            .get(key)
+            // A different length means the file changed since
+            .filter(|saved| saved.chars == self.chars.len())
+            .map(|saved| saved.position)
+            .filter(|position| *position != self.start)
+    }
+
+    pub fn save_position(&self) -> Result<()> {
+        let Some(key) = &self.key else {
+            return Ok(());
+        };
+        let mut positions = TextPositions::load();
+        positions.positions.insert(
+            key.clone(),
+            SavedPosition {
+                position: self.position,
+                chars: self.chars.len(),
+            },
+        );
+        positions.save()
+    }
+}
+
+/// The positions reached in files, by their canonical path
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct TextPositions {
+    positions: BTreeMap<String, SavedPosition>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct SavedPosition {
+    position: usize,
+    /// The length of the text, to notice when the file changes
+    chars: usize,
+}
+
+impl TextPositions {
+    fn load() -> Self {
+        let path = data_dir().join(POSITIONS_FILE);
+        let Ok(contents) = fs::read_to_string(&path) else {
+            return Self::default();
+        };
+
+        serde_json::from_str(&contents).unwrap_or_else(|err| {
+            log::warn!(
+                "Ignoring invalid text positions {}: {}",
+                path.display(),
+                err
+            );
+            Self::default()
+        })
+    }
+
+    fn save(&self) -> Result<()> {
+        let dir = data_dir();
+        create_dir_all(&dir).wrap_err("Creating data directory failed.")?;
+        let contents = serde_json::to_string_pretty(self)?;
+        fs::write(dir.join(POSITIONS_FILE), contents).wrap_err("Writing text positions failed.")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parts_end_at_words_and_wrap_around() {
+        let mut text = CustomText::new(
+            "test".to_string(),
+            None,
+            "one two three four",
+            None,
+            Some(5),
+        )
+        .unwrap();
+        assert_eq!(text.part(), "one two");
+
+        assert!(!text.finish_part());
+        text.next_part();
+        assert_eq!(text.part(), "three");
+
+        text.stop_at(2);
+        text.next_part();
+        assert_eq!(text.part(), "three");
+
+        text.finish_part();
+        text.next_part();
+        assert_eq!(text.part(), "four");
+        assert!(text.finish_part());
+    }
+}
@@ -0,0 +1,247 @@
+use crate::history::TestResult;
+use crate::options::Labeled;
+use crate::stats::{consistency, format_date, format_duration, sparkline, wpm_per_second};
+use color_eyre::Result;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ExportFormat {
+    Json,
+    /// One row per test
+    Csv,
+    /// One row per keystroke
+    KeystrokesCsv,
+    /// A small text card per test, for sharing
+    Card,
+}
+
+const RESULTS_CSV_HEADER: &str =
+    "timestamp,mode,number_of_words,difficulty,duration_secs,wpm,accuracy,correct_chars,errors,layout,idle_pauses,long_gaps";
+const KEYSTROKES_CSV_HEADER: &str = "timestamp,index,time_ms,expected,typed,error";
+/// The most seconds the sparkline of a card shows, longer tests are averaged
+const CARD_SPARKLINE_WIDTH: usize = 30;
+
+pub fn export(results: &[TestResult], format: ExportFormat) -> Result<String> {
+    Ok(match format {
+        ExportFormat::Json => serde_json::to_string_pretty(results)? + "\n",
+        ExportFormat::Csv => results_to_csv(results),
+        ExportFormat::KeystrokesCsv => keystrokes_to_csv(results),
+        ExportFormat::Card => results
+            .iter()
+            .map(summary_card)
+            .collect::<Vec<_>>()
+            .join("\n"),
+    })
+}
+
+/// A boxed summary of a test with a sparkline of its speed, e.g.
+///
+/// ```text
+/// ╭─ typirst ───────────────────────────────╮
+/// │ words · 25 words · lowercase            │
+/// │ 72 WPM · 96% accuracy · 81% consistency │
+/// │ ▅▇█▆▅▄▆▇▆▅▃▆▇█▇▆▅▆▇▆                    │
+/// │ 2024-03-09 14:05 · 21s                  │
+/// ╰─────────────────────────────────────────╯
+/// ```
+pub fn summary_card(result: &TestResult) -> String {
+    let wpm = wpm_per_second(result);
+    let group = wpm.len().div_ceil(CARD_SPARKLINE_WIDTH).max(1);
+    let averaged: Vec<f64> = wpm
+        .chunks(group)
+        .map(|chunk| chunk.iter().sum::<f64>() / chunk.len() as f64)
+        .collect();
+
+    let mut lines = vec![format!(
+        "{} · {} words",
+        result.mode, result.number_of_words
+    )];
+    if result.difficulty != "none" {
+        lines[0].push_str(&format!(" · {}", result.difficulty));
+    }
+    lines.extend([
+        format!(
+            "{:.0} WPM · {:.0}% accuracy · {:.0}% consistency",
+            result.wpm,
+            result.accuracy,
+            consistency(&wpm)
+        ),
+        sparkline(&averaged),
+        format!(
+            "{} · {}",
+            format_date(result.timestamp),
+            format_duration(result.duration_secs)
+        ),
+    ]);
+
+    let title = "─ typirst ";
+    let width = lines
+        .iter()
+        .map(|line| line.chars().count())
+        .max()
+        .unwrap_or_default()
+        .max(title.chars().count());
+    let mut card = format!(
+        "╭{}{}╮\n",
+        title,
+        "─".repeat(width + 2 - title.chars().count())
+    );
+    for line in lines {
+        let padding = width - line.chars().count();
+        card.push_str(&format!("│ {}{} │\n", line, " ".repeat(padding)));
+    }
+    card.push_str(&format!("╰{}╯\n", "─".repeat(width + 2)));
+    card
+}
+
+fn results_to_csv(results: &[TestResult]) -> String {
+    let mut csv = format!("{}\n", RESULTS_CSV_HEADER);
+    for result in results {
+        csv.push_str(&format!(
+            "{},{},{},{},{:.3},{:.2},{:.2},{},{},{},{},{}\n",
+            result.timestamp,
+            csv_field(&result.mode),
+            result.number_of_words,
+            csv_field(&result.difficulty),
+            result.duration_secs,
+            result.wpm,
+            result.accuracy,
+            result.correct_chars,
+            result.errors,
+            result.layout.label(),
+            result.idle_pauses,
+            result.long_gaps,
+        ));
+    }
+    csv
+}
+
+fn keystrokes_to_csv(results: &[TestResult]) -> String {
+    let mut csv = format!("{}\n", KEYSTROKES_CSV_HEADER);
+    for result in results {
+        for (idx, keystroke) in result.keystrokes.iter().enumerate() {
+            csv.push_str(&format!(
+                "{},{},{},{},{},{}\n",
+                result.timestamp,
+                idx,
+                keystroke.time_ms,
+                csv_field(&keystroke.expected.to_string()),
+                csv_field(&keystroke.typed.to_string()),
+                keystroke.error,
+            ));
+        }
+    }
+    csv
+}
+
+/// Quotes a CSV field if it contains a separator, quote or line break
+fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::history::Keystroke;
+    use crate::layout::KeyboardLayout;
+
+    fn result(mode: &str, keystrokes: &str) -> TestResult {
+        TestResult {
+            timestamp: 1_700_000_000,
+            mode: mode.to_string(),
+            number_of_words: 3,
+            difficulty: "none".to_string(),
+            duration_secs: 2.0,
+            wpm: 42.0,
+            accuracy: 100.0,
+            correct_chars: keystrokes.chars().count(),
+            errors: 0,
+            keystrokes: keystrokes
+                .chars()
+                .enumerate()
+                .map(|(idx, c)| Keystroke {
+                    time_ms: idx as u64 * 100,
+                    expected: c,
+                    typed: c,
+                    error: false,
+                })
+                .collect(),
+            layout: KeyboardLayout::Qwerty,
+            idle_pauses: 0,
+            long_gaps: 0,
+        }
+    }
+
+    #[test]
+    fn csv_fields_are_quoted_when_needed() {
+        assert_eq!(csv_field("words"), "words");
+        assert_eq!(csv_field("a,b"), "\"a,b\"");
+        assert_eq!(csv_field("say \"hi\""), "\"say \"\"hi\"\"\"");
+        assert_eq!(csv_field("\n"), "\"\n\"");
+        assert_eq!(csv_field("\r"), "\"\r\"");
+    }
+
+    #[test]
+    fn csv_rows_quote_custom_text() {
+        let results = [result("my, mode", ",\"\n")];
+        let csv = export(&results, ExportFormat::Csv).unwrap();
+        assert_eq!(
+            csv.lines().nth(1),
+            Some("1700000000,\"my, mode\",3,none,2.000,42.00,100.00,3,0,qwerty,0,0")
+        );
+
+        let keystrokes = export(&results, ExportFormat::KeystrokesCsv).unwrap();
+        assert_eq!(
+            keystrokes,
+            format!(
+                "{}\n{}",
+                KEYSTROKES_CSV_HEADER,
+                "1700000000,0,0,\",\",\",\",false\n\
+                 1700000000,1,100,\"\"\"\",\"\"\"\",false\n\
+                 1700000000,2,200,\"\n\",\"\n\",false\n"
+            )
+        );
+    }
+
+    #[test]
+    fn summary_card_boxes_the_result() {
+        let card = summary_card(&result("words", "abcdefghijklmnopqrst"));
+        // 10 correct keys in each of the 2 seconds
+        assert_eq!(
+            card,
+            "╭─ typirst ─────────────────────────────────╮\n\
+             │ words · 3 words                           │\n\
+             │ 42 WPM · 100% accuracy · 100% consistency │\n\
+             │ ██                                        │\n\
+             │ 2023-11-14 22:13 · 2s                     │\n\
+             ╰───────────────────────────────────────────╯\n"
+        );
+
+        let mut long = result("words", &"a".repeat(90));
+        long.duration_secs = 90.0;
+        for (idx, keystroke) in long.keystrokes.iter_mut().enumerate() {
+            keystroke.time_ms = idx as u64 * 1000;
+        }
+        let sparkline = summary_card(&long).lines().nth(3).unwrap().to_string();
+        assert_eq!(
+            sparkline.trim_matches(['│', ' ']).chars().count(),
+            CARD_SPARKLINE_WIDTH
+        );
+    }
+
+    #[test]
+    fn json_export_is_an_array_of_results() {
+        let json = export(&[result("words", "ab")], ExportFormat::Json).unwrap();
+        let value: serde_json::Value = serde_json::from_str(&json).unwrap();
+        let tests = value.as_array().unwrap();
+        assert_eq!(tests.len(), 1);
+        assert_eq!(tests[0]["mode"], "words");
+        assert_eq!(tests[0]["layout"], "qwerty");
+        assert_eq!(tests[0]["keystrokes"][1]["expected"], "b");
+        assert_eq!(tests[0]["idle_pauses"], 0);
+        assert_eq!(export(&[], ExportFormat::Json).unwrap(), "[]\n");
+    }
+}
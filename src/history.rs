@@ -0,0 +1,162 @@
+use crate::config::data_dir;
+use crate::layout::KeyboardLayout;
+use color_eyre::{eyre::WrapErr, Result};
+use serde::{Deserialize, Serialize};
+use std::fs::{self, create_dir_all, OpenOptions};
+use std::io::Write;
+use std::path::Path;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+const HISTORY_FILE: &str = "history.jsonl";
+
+/// A finished test, as stored in the history and exported by `typirst export`.
+/// The field names are part of the documented export schema, so they must not
+/// be renamed.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TestResult {
+    /// Unix timestamp (seconds) of when the test was finished
+    pub timestamp: u64,
+    /// The kind of text that was typed, e.g. "words"
+    pub mode: String,
+    pub number_of_words: usize,
+    pub difficulty: String,
+    pub duration_secs: f64,
+    pub wpm: f64,
+    /// Percentage of keystrokes that were correct
+    pub accuracy: f64,
+    pub correct_chars: usize,
+    pub errors: usize,
+    pub keystrokes: Vec<Keystroke>,
+    /// The keyboard layout keystrokes are assigned to fingers with. Older
+    /// results without one were typed on qwerty.
+    #[serde(default)]
+    pub layout: KeyboardLayout,
+    /// How many times the test was paused for going idle. Such results do
+    /// not count as personal bests.
+    #[serde(default)]
+    pub idle_pauses: usize,
+    /// How many gaps between keystrokes came close to going idle. They are
+    /// timed, so such results do not count as personal bests either.
+    #[serde(default)]
+    pub long_gaps: usize,
+}
+
+impl TestResult {
+    /// Whether the test was typed without going idle or long gaps
+    pub fn is_valid(&self) -> bool {
+        self.idle_pauses == 0 && self.long_gaps == 0
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Keystroke {
+    /// Milliseconds since the first keystroke of the test
+    pub time_ms: u64,
+    pub expected: char,
+    pub typed: char,
+    pub error: bool,
+}
+
+/// The current unix timestamp, in seconds
+pub fn unix_now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |d| d.as_secs())
+}
+
+/// Loads all the stored results, oldest first. Lines that cannot be parsed
+/// are skipped.
+pub fn load_history() -> Result<Vec<TestResult>> {
+    load_history_from(&data_dir().join(HISTORY_FILE))
+}
+
+fn load_history_from(path: &Path) -> Result<Vec<TestResult>> {
+    if !path.exists() {
+        return Ok(vec![]);
+    }
+
+    let contents = fs::read_to_string(path).wrap_err("Reading history failed.")?;
+    Ok(contents
+        .lines()
+        .filter(|line| !line.trim().is_empty())
+        .filter_map(|line| match serde_json::from_str(line) {
+            Ok(result) => Some(result),
+            Err(err) => {
+                log::warn!("Skipping invalid history entry: {}", err);
+                None
+            }
+        })
+        .collect())
+}
+
+pub fn append_to_history(result: &TestResult) -> Result<()> {
+    let dir = data_dir();
+    create_dir_all(&dir).wrap_err("Creating data directory failed.")?;
+    append_to(&dir.join(HISTORY_FILE), result)
+}
+
+fn append_to(path: &Path, result: &TestResult) -> Result<()> {
+    let mut file = OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)
+        .wrap_err("Opening history failed.")?;
+    writeln!(file, "{}", serde_json::to_string(result)?).wrap_err("Writing history failed.")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn history_round_trips_and_skips_invalid_lines() {
+        let path =
+            std::env::temp_dir().join(format!("typirst-history-{}.jsonl", std::process::id()));
+        let _ = fs::remove_file(&path);
+        assert!(load_history_from(&path).unwrap().is_empty());
+
+        let result = TestResult {
+            timestamp: 1_700_000_000,
+            mode: "custom text".to_string(),
+            number_of_words: 2,
+            difficulty: "none".to_string(),
+            duration_secs: 1.5,
+            wpm: 16.0,
+            accuracy: 80.0,
+            correct_chars: 4,
+            errors: 1,
+            keystrokes: vec![Keystroke {
+                time_ms: 0,
+                expected: '"',
+                typed: ',',
+                error: true,
+            }],
+            layout: KeyboardLayout::Dvorak,
+            idle_pauses: 1,
+            long_gaps: 0,
+        };
+        append_to(&path, &result).unwrap();
+        fs::write(
+            &path,
+            fs::read_to_string(&path).unwrap() + "not json\n\n{\"timestamp\":1}\n",
+        )
+        .unwrap();
+        append_to(&path, &result).unwrap();
+
+        let loaded = load_history_from(&path).unwrap();
+        fs::remove_file(&path).unwrap();
+        assert_eq!(loaded.len(), 2);
+        assert_eq!(
+            serde_json::to_string(&loaded[1]).unwrap(),
+            serde_json::to_string(&result).unwrap()
+        );
+    }
+
+    #[test]
+    fn older_results_get_defaults() {
+        let line = r#"{"timestamp":1,"mode":"words","number_of_words":10,"difficulty":"lowercase","duration_secs":5.0,"wpm":30.0,"accuracy":100.0,"correct_chars":25,"errors":0,"keystrokes":[]}"#;
+        let result: TestResult = serde_json::from_str(line).unwrap();
+        assert_eq!(result.layout, KeyboardLayout::Qwerty);
+        assert!(result.is_valid());
+    }
+}
@@ -0,0 +1,480 @@
+use color_eyre::{eyre::eyre, Result};
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fmt;
+
+/// Everything that can be triggered with a key, apart from typing
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Action {
+    Pause,
+    Resume,
+    /// Ends the test in zen mode, where there is no text to finish
+    Finish,
+    Restart,
+    /// Restarts with new text from any screen, including while typing
+    QuickRestart,
+    /// Restarts with the same text
+    Retry,
+    Quit,
+    Export,
+    /// Copies a summary card of the result and saves it to a file
+    Share,
+    /// Switches between the views of the results screen
+    ResultsView,
+    /// Opens or closes the history browser
+    History,
+    /// Opens or closes the lesson picker
+    Lessons,
+    /// Starts a drill of the slow and mistyped words of the last test
+    Drill,
+    /// Resumes the custom text where the last session stopped
+    ResumeText,
+    /// Types the custom text from the beginning instead of resuming it
+    StartOver,
+    /// Moves up in the lists of lessons, tests and words
+    Previous,
+    /// Moves down in the lists of lessons, tests and words
+    Next,
+    /// Starts the selected lesson
+    Select,
+    /// Closes the lesson picker or the history browser
+    Back,
+    NumberOfWords,
+    TextMode,
+    Capitals,
+    Numbers,
+    Punctuation,
+    Brackets,
+    Highlight,
+    VisibleLines,
+    Scrolling,
+    Caret,
+    Keyboard,
+    LiveStats,
+    DailyGoal,
+    Layout,
+}
+
+/// The screens key bindings apply to. Keys only conflict within a screen.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Screen {
+    Typing,
+    Paused,
+    Results,
+    History,
+    Lessons,
+    /// The offer to resume the text where the last session stopped
+    ResumeText,
+}
+
+impl Action {
+    const ALL: [Action; 33] = [
+        Action::Pause,
+        Action::Resume,
+        Action::Finish,
+        Action::Restart,
+        Action::QuickRestart,
+        Action::Retry,
+        Action::Quit,
+        Action::Export,
+        Action::Share,
+        Action::ResultsView,
+        Action::History,
+        Action::Lessons,
+        Action::Drill,
+        Action::ResumeText,
+        Action::StartOver,
+        Action::Previous,
+        Action::Next,
+        Action::Select,
+        Action::Back,
+        Action::NumberOfWords,
+        Action::TextMode,
+        Action::Capitals,
+        Action::Numbers,
+        Action::Punctuation,
+        Action::Brackets,
+        Action::Highlight,
+        Action::VisibleLines,
+        Action::Scrolling,
+        Action::Caret,
+        Action::Keyboard,
+        Action::LiveStats,
+        Action::DailyGoal,
+        Action::Layout,
+    ];
+
+    fn screens(&self) -> &'static [Screen] {
+        match self {
+            Action::Pause | Action::Finish => &[Screen::Typing],
+            Action::QuickRestart => &[Screen::Typing, Screen::Paused, Screen::Results],
+            Action::Restart | Action::Retry | Action::Quit => &[Screen::Paused, Screen::Results],
+            Action::Export | Action::Share | Action::Drill => &[Screen::Results],
+            Action::ResultsView => &[Screen::Results, Screen::History],
+            Action::History => &[Screen::Paused, Screen::Results, Screen::History],
+            Action::Lessons => &[Screen::Paused, Screen::Results, Screen::Lessons],
+            Action::ResumeText | Action::StartOver => &[Screen::ResumeText],
+            Action::Previous | Action::Next => &[Screen::Results, Screen::History, Screen::Lessons],
+            Action::Select => &[Screen::Lessons],
+            Action::Back => &[Screen::History, Screen::Lessons],
+            _ => &[Screen::Paused],
+        }
+    }
+
+    fn default_keys(&self) -> Vec<Key> {
+        let key = match self {
+            Action::Pause | Action::Resume => KeyCode::Esc,
+            Action::Finish => return vec![Key::new(KeyCode::Char('d'), KeyModifiers::CONTROL)],
+            Action::Restart => KeyCode::Char('r'),
+            Action::QuickRestart => KeyCode::Tab,
+            Action::Retry => KeyCode::Char('t'),
+            Action::Quit => KeyCode::Char('q'),
+            Action::Export => KeyCode::Char('e'),
+            Action::Share => KeyCode::Char('s'),
+            Action::ResultsView => KeyCode::Char('v'),
+            Action::History => KeyCode::Char('H'),
+            Action::Lessons => KeyCode::Char('L'),
+            Action::Drill => KeyCode::Char('d'),
+            Action::ResumeText => KeyCode::Enter,
+            Action::StartOver | Action::Back => KeyCode::Esc,
+            Action::Previous => {
+                return vec![Key::plain(KeyCode::Up), Key::plain(KeyCode::Char('k'))]
+            }
+            Action::Next => return vec![Key::plain(KeyCode::Down), Key::plain(KeyCode::Char('j'))],
+            Action::Select => KeyCode::Enter,
+            Action::NumberOfWords => KeyCode::Char('w'),
+            Action::TextMode => KeyCode::Char('m'),
+            Action::Capitals => KeyCode::Char('u'),
+            Action::Numbers => KeyCode::Char('n'),
+            Action::Punctuation => KeyCode::Char('p'),
+            Action::Brackets => KeyCode::Char('b'),
+            Action::Highlight => KeyCode::Char('h'),
+            Action::VisibleLines => KeyCode::Char('l'),
+            Action::Scrolling => KeyCode::Char('s'),
+            Action::Caret => KeyCode::Char('c'),
+            Action::Keyboard => KeyCode::Char('o'),
+            Action::LiveStats => KeyCode::Char('i'),
+            Action::DailyGoal => KeyCode::Char('g'),
+            Action::Layout => KeyCode::Char('k'),
+        };
+        vec![Key::plain(key)]
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Key {
+    code: KeyCode,
+    modifiers: KeyModifiers,
+}
+
+impl Key {
+    fn new(code: KeyCode, modifiers: KeyModifiers) -> Self {
+        Self { code, modifiers }
+    }
+
+    fn plain(code: KeyCode) -> Self {
+        Self::new(code, KeyModifiers::NONE)
+    }
+
+    /// Parses keys like `q`, `Esc`, `tab`, `F5`, `ctrl-r` or `Alt-Enter`. Key
+    /// names are case insensitive, single characters are not.
+    pub fn parse(s: &str) -> Result<Self> {
+        let mut modifiers = KeyModifiers::NONE;
+        let mut rest = s;
+        loop {
+            let lowercase = rest.to_lowercase();
+            if lowercase.starts_with("ctrl-") {
+                modifiers |= KeyModifiers::CONTROL;
+                rest = &rest[5..];
+            } else if lowercase.starts_with("alt-") {
+                modifiers |= KeyModifiers::ALT;
+                rest = &rest[4..];
+            } else {
+                break;
+            }
+        }
+
+        let name = if rest.chars().count() > 1 {
+            rest.to_lowercase()
+        } else {
+            rest.to_string()
+        };
+        let code = match name.as_str() {
+            "esc" => KeyCode::Esc,
+            "tab" => KeyCode::Tab,
+            "enter" => KeyCode::Enter,
+            "backspace" => KeyCode::Backspace,
+            "space" => KeyCode::Char(' '),
+            "up" => KeyCode::Up,
+            "down" => KeyCode::Down,
+            f if f.len() > 1 && f.starts_with('f') => f[1..]
+                .parse()
+                .map(KeyCode::F)
+                .map_err(|_| eyre!("Unknown key '{}'", s))?,
+            c if c.chars().count() == 1 => KeyCode::Char(c.chars().next().unwrap()),
+            _ => return Err(eyre!("Unknown key '{}'", s)),
+        };
+        Ok(Self::new(code, modifiers))
+    }
+
+    pub fn matches(&self, event: &KeyEvent) -> bool {
+        // Shift is part of the character itself, e.g. `R`
+        let modifiers = event.modifiers - KeyModifiers::SHIFT;
+        let code = match event.code {
+            // Some terminals report shift-tab as a plain tab with shift
+            KeyCode::BackTab => KeyCode::Tab,
+            code => code,
+        };
+        self.code == code && self.modifiers == modifiers
+    }
+
+    /// Whether pressing the key types something, in which case it cannot be
+    /// used while typing
+    fn types_text(&self) -> bool {
+        self.modifiers.is_empty()
+            && matches!(
+                self.code,
+                KeyCode::Char(_) | KeyCode::Enter | KeyCode::Backspace
+            )
+    }
+}
+
+impl fmt::Display for Key {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if self.modifiers.contains(KeyModifiers::CONTROL) {
+            write!(f, "Ctrl-")?;
+        }
+        if self.modifiers.contains(KeyModifiers::ALT) {
+            write!(f, "Alt-")?;
+        }
+        match self.code {
+            KeyCode::Esc => write!(f, "Esc"),
+            KeyCode::Tab => write!(f, "Tab"),
+            KeyCode::Enter => write!(f, "Enter"),
+            KeyCode::Backspace => write!(f, "Backspace"),
+            KeyCode::Char(' ') => write!(f, "Space"),
+            KeyCode::Up => write!(f, "Up"),
+            KeyCode::Down => write!(f, "Down"),
+            KeyCode::Char(c) => write!(f, "{}", c),
+            KeyCode::F(n) => write!(f, "F{}", n),
+            _ => write!(f, "?"),
+        }
+    }
+}
+
+/// Parses the keys of a binding, separated by spaces, e.g. `Up k`
+fn parse_keys(s: &str) -> Result<Vec<Key>> {
+    let keys: Vec<Key> = s
+        .split_whitespace()
+        .map(Key::parse)
+        .collect::<Result<_>>()?;
+    if keys.is_empty() {
+        return Err(eyre!("No key given in '{}'", s));
+    }
+    Ok(keys)
+}
+
+fn format_keys(keys: &[Key]) -> String {
+    keys.iter()
+        .map(Key::to_string)
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+#[derive(Debug, Clone)]
+pub struct Keymap {
+    bindings: BTreeMap<Action, Vec<Key>>,
+}
+
+impl Default for Keymap {
+    fn default() -> Self {
+        Self {
+            bindings: Action::ALL
+                .iter()
+                .map(|action| (*action, action.default_keys()))
+                .collect(),
+        }
+    }
+}
+
+impl Keymap {
+    /// Creates a keymap from the defaults and the bindings in the config,
+    /// failing if a key cannot be parsed or is bound twice on the same screen.
+    pub fn new(overrides: &BTreeMap<Action, String>) -> Result<Self> {
+        let mut keymap = Self::default();
+        for (action, keys) in overrides {
+            keymap.bindings.insert(*action, parse_keys(keys)?);
+        }
+        keymap.validate()?;
+        Ok(keymap)
+    }
+
+    fn validate(&self) -> Result<()> {
+        for (action, keys) in &self.bindings {
+            for key in keys {
+                if action.screens().contains(&Screen::Typing) && key.types_text() {
+                    return Err(eyre!(
+                        "Key '{}' for {:?} cannot be used while typing",
+                        key,
+                        action
+                    ));
+                }
+
+                let conflict = self.bindings.iter().find(|(other, other_keys)| {
+                    other > &action
+                        && other_keys.contains(key)
+                        && other.screens().iter().any(|s| action.screens().contains(s))
+                });
+                if let Some((other, _)) = conflict {
+                    return Err(eyre!(
+                        "Key '{}' is bound to both {:?} and {:?}",
+                        key,
+                        action,
+                        other
+                    ));
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// The bindings that differ from the defaults, as stored in the config
+    pub fn overrides(&self) -> BTreeMap<Action, String> {
+        self.bindings
+            .iter()
+            .filter(|(action, keys)| action.default_keys() != **keys)
+            .map(|(action, keys)| (*action, format_keys(keys)))
+            .collect()
+    }
+
+    /// The first key bound to the action, as shown on screen
+    pub fn key(&self, action: Action) -> Key {
+        self.bindings[&action][0]
+    }
+
+    /// The action bound to the pressed key on the given screen, if any
+    pub fn action(&self, screen: Screen, event: &KeyEvent) -> Option<Action> {
+        self.bindings
+            .iter()
+            .find(|(action, keys)| {
+                action.screens().contains(&screen) && keys.iter().any(|key| key.matches(event))
+            })
+            .map(|(action, _)| *action)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn overrides(bindings: &[(Action, &str)]) -> BTreeMap<Action, String> {
+        bindings
+            .iter()
+            .map(|(action, key)| (*action, key.to_string()))
+            .collect()
+    }
+
+    #[test]
+    fn parses_modifiers_and_names() {
+        let key = Key::parse("Ctrl-alt-R").unwrap();
+        assert_eq!(
+            key,
+            Key::new(
+                KeyCode::Char('R'),
+                KeyModifiers::CONTROL | KeyModifiers::ALT
+            )
+        );
+        assert_eq!(key.to_string(), "Ctrl-Alt-R");
+        assert_eq!(
+            Key::parse("ESC").unwrap(),
+            Key::new(KeyCode::Esc, KeyModifiers::NONE)
+        );
+        assert_eq!(
+            Key::parse("f12").unwrap(),
+            Key::new(KeyCode::F(12), KeyModifiers::NONE)
+        );
+        assert_eq!(
+            Key::parse("space").unwrap(),
+            Key::new(KeyCode::Char(' '), KeyModifiers::NONE)
+        );
+        // Single characters keep their case
+        assert_ne!(Key::parse("r").unwrap(), Key::parse("R").unwrap());
+    }
+
+    #[test]
+    fn rejects_unknown_keys() {
+        assert!(Key::parse("escape").is_err());
+        assert!(Key::parse("fx").is_err());
+        assert!(Key::parse("ctrl-").is_err());
+        assert!(Keymap::new(&overrides(&[(Action::Quit, "nope")])).is_err());
+    }
+
+    #[test]
+    fn rejects_conflicts_on_the_same_screen() {
+        // Restart and quit are both on the pause menu
+        assert!(Keymap::new(&overrides(&[(Action::Quit, "r")])).is_err());
+        // Export is only on the results screen, capitals only in the pause menu
+        let keymap = Keymap::new(&overrides(&[(Action::Export, "u")])).unwrap();
+        assert_eq!(keymap.overrides(), overrides(&[(Action::Export, "u")]));
+        // The pause key must not type a character
+        assert!(Keymap::new(&overrides(&[(Action::Pause, "p")])).is_err());
+        assert!(Keymap::new(&overrides(&[(Action::Pause, "ctrl-p")])).is_ok());
+    }
+
+    #[test]
+    fn finds_the_action_for_a_screen() {
+        let keymap = Keymap::default();
+        let esc = KeyEvent::new(KeyCode::Esc, KeyModifiers::NONE);
+        assert_eq!(keymap.action(Screen::Typing, &esc), Some(Action::Pause));
+        assert_eq!(keymap.action(Screen::Paused, &esc), Some(Action::Resume));
+        assert_eq!(
+            keymap.action(Screen::ResumeText, &esc),
+            Some(Action::StartOver)
+        );
+        let shifted = KeyEvent::new(KeyCode::Char('H'), KeyModifiers::SHIFT);
+        assert_eq!(
+            keymap.action(Screen::Results, &shifted),
+            Some(Action::History)
+        );
+    }
+
+    #[test]
+    fn binds_several_keys_to_an_action() {
+        let keymap = Keymap::default();
+        let event = |code| KeyEvent::new(code, KeyModifiers::NONE);
+        assert_eq!(
+            keymap.action(Screen::History, &event(KeyCode::Char('k'))),
+            Some(Action::Previous)
+        );
+        assert_eq!(
+            keymap.action(Screen::Lessons, &event(KeyCode::Up)),
+            Some(Action::Previous)
+        );
+        assert_eq!(
+            keymap.action(Screen::Lessons, &event(KeyCode::Esc)),
+            Some(Action::Back)
+        );
+        // The navigation keys are free for the options in the pause menu
+        assert_eq!(
+            keymap.action(Screen::Paused, &event(KeyCode::Char('k'))),
+            Some(Action::Layout)
+        );
+
+        let keymap = Keymap::new(&overrides(&[(Action::Next, "down  n")])).unwrap();
+        assert_eq!(
+            keymap.action(Screen::History, &event(KeyCode::Char('n'))),
+            Some(Action::Next)
+        );
+        assert_eq!(
+            keymap.action(Screen::History, &event(KeyCode::Char('j'))),
+            None
+        );
+        assert_eq!(keymap.overrides(), overrides(&[(Action::Next, "Down n")]));
+
+        // Each of the keys conflicts with the other actions on the screen
+        assert!(Keymap::new(&overrides(&[(Action::Next, "Down q")])).is_err());
+        assert!(Keymap::new(&overrides(&[(Action::Next, " ")])).is_err());
+    }
+}
@@ -0,0 +1,274 @@
+use crate::config::data_dir;
+use crate::options::Labeled;
+use color_eyre::{eyre::WrapErr, Result};
+use rand::seq::SliceRandom;
+use rand::{thread_rng, Rng};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs::{self, create_dir_all};
+
+const PROGRESS_FILE: &str = "layout_progress.json";
+/// Keys unlocked when starting to learn a layout
+pub const INITIAL_KEYS: usize = 6;
+/// Below this many typeable words from the word list, made up ones are used
+const MIN_REAL_WORDS: usize = 10;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum KeyboardLayout {
+    #[default]
+    Qwerty,
+    Colemak,
+    Dvorak,
+    Workman,
+}
+impl Labeled for KeyboardLayout {
+    fn label(&self) -> String {
+        match self {
+            KeyboardLayout::Qwerty => "qwerty".to_string(),
+            KeyboardLayout::Colemak => "colemak".to_string(),
+            KeyboardLayout::Dvorak => "dvorak".to_string(),
+            KeyboardLayout::Workman => "workman".to_string(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Finger {
+    LeftPinky,
+    LeftRing,
+    LeftMiddle,
+    LeftIndex,
+    RightIndex,
+    RightMiddle,
+    RightRing,
+    RightPinky,
+}
+impl Labeled for Finger {
+    fn label(&self) -> String {
+        match self {
+            Finger::LeftPinky => "left pinky".to_string(),
+            Finger::LeftRing => "left ring".to_string(),
+            Finger::LeftMiddle => "left middle".to_string(),
+            Finger::LeftIndex => "left index".to_string(),
+            Finger::RightIndex => "right index".to_string(),
+            Finger::RightMiddle => "right middle".to_string(),
+            Finger::RightRing => "right ring".to_string(),
+            Finger::RightPinky => "right pinky".to_string(),
+        }
+    }
+}
+
+impl Finger {
+    pub fn hand(&self) -> Hand {
+        match self {
+            Finger::LeftPinky | Finger::LeftRing | Finger::LeftMiddle | Finger::LeftIndex => {
+                Hand::Left
+            }
+            _ => Hand::Right,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Hand {
+    Left,
+    Right,
+}
+impl Labeled for Hand {
+    fn label(&self) -> String {
+        match self {
+            Hand::Left => "left hand".to_string(),
+            Hand::Right => "right hand".to_string(),
+        }
+    }
+}
+
+/// The finger that presses each column of the main rows, in touch typing
+const COLUMN_FINGERS: [Finger; 10] = [
+    Finger::LeftPinky,
+    Finger::LeftRing,
+    Finger::LeftMiddle,
+    Finger::LeftIndex,
+    Finger::LeftIndex,
+    Finger::RightIndex,
+    Finger::RightIndex,
+    Finger::RightMiddle,
+    Finger::RightRing,
+    Finger::RightPinky,
+];
+/// The order columns are learnt in within a row: from the index fingers
+/// outwards, with the stretches to the middle columns last
+const COLUMN_ORDER: [usize; 10] = [3, 6, 2, 7, 1, 8, 0, 9, 4, 5];
+
+impl KeyboardLayout {
+    /// The top, home and bottom rows of letter and punctuation keys
+    pub fn rows(&self) -> [&'static str; 3] {
+        match self {
+            KeyboardLayout::Qwerty => ["qwertyuiop", "asdfghjkl;", "zxcvbnm,./"],
+            KeyboardLayout::Colemak => ["qwfpgjluy;", "arstdhneio", "zxcvbkm,./"],
+            KeyboardLayout::Dvorak => ["',.pyfgcrl", "aoeuidhtns", ";qjkxbmwvz"],
+            KeyboardLayout::Workman => ["qdrwbjfup;", "ashtgyneoi", "zxmcvkl,./"],
+        }
+    }
+
+    /// The row (0 is the top row) and column of the key that types `c`
+    pub fn position(&self, c: char) -> Option<(usize, usize)> {
+        let c = c.to_lowercase().next()?;
+        self.rows()
+            .iter()
+            .enumerate()
+            .find_map(|(row, keys)| keys.chars().position(|k| k == c).map(|col| (row, col)))
+    }
+
+    pub fn finger(&self, c: char) -> Option<Finger> {
+        self.position(c).map(|(_, col)| COLUMN_FINGERS[col])
+    }
+
+    /// The letters in the order they are unlocked when learning the layout:
+    /// the home row first, then the top and the bottom row
+    pub fn learning_order(&self) -> Vec<char> {
+        let rows = self.rows();
+        [1, 0, 2]
+            .iter()
+            .flat_map(|&row| {
+                let keys: Vec<char> = rows[row].chars().collect();
+                COLUMN_ORDER.iter().map(move |&col| keys[col])
+            })
+            .filter(|c| c.is_alphabetic())
+            .collect()
+    }
+}
+
+/// How many keys of each layout have been unlocked in the learn layout mode
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct LayoutProgress {
+    unlocked: BTreeMap<KeyboardLayout, usize>,
+}
+
+impl LayoutProgress {
+    /// Loads the progress from the data directory, starting over if there is
+    /// none or it cannot be parsed
+    pub fn load() -> Self {
+        let path = data_dir().join(PROGRESS_FILE);
+        let Ok(contents) = fs::read_to_string(&path) else {
+            return Self::default();
+        };
+
+        serde_json::from_str(&contents).unwrap_or_else(|err| {
+            log::warn!(
+                "Ignoring invalid layout progress {}: {}",
+                path.display(),
+                err
+            );
+            Self::default()
+        })
+    }
+
+    pub fn save(&self) -> Result<()> {
+        let dir = data_dir();
+        create_dir_all(&dir).wrap_err("Creating data directory failed.")?;
+        let contents = serde_json::to_string_pretty(self)?;
+        fs::write(dir.join(PROGRESS_FILE), contents).wrap_err("Writing layout progress failed.")
+    }
+
+    /// The unlocked keys of the layout, in the order they were unlocked
+    pub fn unlocked_keys(&self, layout: KeyboardLayout) -> Vec<char> {
+        let count = self.unlocked.get(&layout).copied().unwrap_or(INITIAL_KEYS);
+        layout.learning_order().into_iter().take(count).collect()
+    }
+
+    /// Unlocks the next key of the layout, returning it unless all the keys
+    /// are unlocked already
+    pub fn unlock_next(&mut self, layout: KeyboardLayout) -> Option<char> {
+        let count = self.unlocked.entry(layout).or_insert(INITIAL_KEYS);
+        let key = layout.learning_order().get(*count).copied()?;
+        *count += 1;
+        Some(key)
+    }
+}
+
+/// Picks `count` words from `words` that can be typed with the unlocked keys,
+/// half of them using the most recently unlocked one. Made up words fill in
+/// when too few real ones can be typed yet.
+pub fn words_with_keys(words: &[String], unlocked: &[char], count: usize) -> Vec<String> {
+    let mut rng = thread_rng();
+    let typeable: Vec<&String> = words
+        .iter()
+        .filter(|w| !w.is_empty() && w.chars().all(|c| unlocked.contains(&c)))
+        .collect();
+    let newest = unlocked.last().copied();
+    let with_newest: Vec<&String> = typeable
+        .iter()
+        .copied()
+        .filter(|w| newest.is_some_and(|n| w.contains(n)))
+        .collect();
+
+    (0..count)
+        .map(|idx| {
+            let pool = if idx % 2 == 0 && !with_newest.is_empty() {
+                &with_newest
+            } else {
+                &typeable
+            };
+            match pool.choose(&mut rng) {
+                Some(word) if typeable.len() >= MIN_REAL_WORDS => word.to_string(),
+                _ => made_up_word(&mut rng, unlocked, idx % 2 == 0),
+            }
+        })
+        .collect()
+}
+
+fn made_up_word(rng: &mut impl Rng, unlocked: &[char], use_newest: bool) -> String {
+    let len = rng.gen_range(2..=5);
+    let mut word: Vec<char> = (0..len).map(|_| *unlocked.choose(rng).unwrap()).collect();
+    if use_newest {
+        word[rng.gen_range(0..len)] = *unlocked.last().unwrap();
+    }
+    word.into_iter().collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn words_only_use_unlocked_keys() {
+        let list: Vec<String> = ["sad", "lass", "fall", "dog", "ask"]
+            .iter()
+            .map(|w| w.to_string())
+            .collect();
+        let unlocked = KeyboardLayout::Qwerty.learning_order()[..INITIAL_KEYS].to_vec();
+        for word in words_with_keys(&list, &unlocked, 50) {
+            assert!(word.chars().all(|c| unlocked.contains(&c)), "{}", word);
+        }
+    }
+
+    #[test]
+    fn learning_order_covers_the_alphabet_starting_on_the_home_row() {
+        for layout in [
+            KeyboardLayout::Qwerty,
+            KeyboardLayout::Colemak,
+            KeyboardLayout::Dvorak,
+            KeyboardLayout::Workman,
+        ] {
+            let mut order = layout.learning_order();
+            assert!(order[..INITIAL_KEYS]
+                .iter()
+                .all(|c| layout.rows()[1].contains(*c)));
+            order.sort_unstable();
+            assert_eq!(
+                order.iter().collect::<String>(),
+                "abcdefghijklmnopqrstuvwxyz"
+            );
+        }
+    }
+
+    #[test]
+    fn fingers_follow_the_layout() {
+        assert_eq!(KeyboardLayout::Qwerty.finger('f'), Some(Finger::LeftIndex));
+        assert_eq!(KeyboardLayout::Colemak.finger('T'), Some(Finger::LeftIndex));
+        assert_eq!(KeyboardLayout::Dvorak.finger('s'), Some(Finger::RightPinky));
+        assert_eq!(KeyboardLayout::Qwerty.finger('1'), None);
+    }
+}
@@ -1,60 +1,175 @@
+pub mod config;
+mod course;
+pub mod custom_text;
+pub mod export;
+pub mod history;
+mod keymap;
+pub mod layout;
+mod ngrams;
+mod numeric;
 mod options;
+mod sentences;
+pub mod stats;
 mod text_generator;
 mod timer;
 pub mod tui;
 mod ui;
 mod utils;
+mod word_stats;
 
 use color_eyre::{eyre::WrapErr, Result};
-use crossterm::event::{self, Event, KeyCode, KeyEvent, KeyEventKind};
-use options::{CyclicOption, Highlight, NumberOfWords, TextDifficulty};
-use ratatui::layout::Rect;
+use config::Config;
+use course::{Course, CourseProgress};
+use crossterm::event::{
+    self, Event, KeyCode, KeyEvent, KeyEventKind, MouseButton, MouseEvent, MouseEventKind,
+};
+use custom_text::CustomText;
+use export::ExportFormat;
+use history::{append_to_history, load_history, unix_now, Keystroke, TestResult};
+use keymap::{Action, Keymap, Screen};
+use layout::{KeyboardLayout, LayoutProgress};
+use ngrams::{valid_ngrams, DEFAULT_NGRAMS};
+use options::Labeled;
+use options::{
+    Caret, CyclicOption, DailyGoal, Frequency, Highlight, LiveStats, NumberOfWords,
+    OnScreenKeyboard, ResultsView, Scrolling, TextMode, TextModifiers, VisibleLines,
+};
+use ratatui::layout::{Position, Rect};
+use ratatui::widgets::ListState;
+use stats::GoalStatus;
+use std::fs;
 use std::time::Duration;
-use text_generator::{Character, TextGenerator};
+use text_generator::{find_words, Character, TextGenerator, Word};
 use timer::Timer;
 use ui::ui;
+use word_stats::{drill_words, word_results, WordResult};
 
 #[derive(Debug)]
 pub struct App {
     lines: Vec<Vec<Character>>,
+    /// The words of the lines
+    words: Vec<Word>,
     stats: Vec<TypingEvent>,
     cur_line: usize,
     position: usize,
     typed_chars: usize,
+    /// Everything typed in zen mode, which has no text to type
+    free_text: String,
     errors: usize,
     pause: bool,
+    /// Whether the test was paused for going idle or losing the focus, when
+    /// only the resume key works
+    auto_paused: bool,
     quit: bool,
     timer: Timer,
     text_generator: TextGenerator,
     number_of_words: CyclicOption<NumberOfWords>,
-    difficulty: CyclicOption<TextDifficulty>,
+    text_mode: CyclicOption<TextMode>,
+    capitals: CyclicOption<Frequency>,
+    numbers: CyclicOption<Frequency>,
+    punctuation: CyclicOption<Frequency>,
+    brackets: CyclicOption<Frequency>,
     highlight: CyclicOption<Highlight>,
+    visible_lines: CyclicOption<VisibleLines>,
+    scrolling: CyclicOption<Scrolling>,
+    caret: CyclicOption<Caret>,
+    keyboard: CyclicOption<OnScreenKeyboard>,
+    live_stats: CyclicOption<LiveStats>,
+    results_view: CyclicOption<ResultsView>,
+    daily_goal: CyclicOption<DailyGoal>,
+    /// The slow and mistyped words of an earlier test, typed in the drill mode
+    drill_words: Vec<String>,
+    /// The configured n-grams, empty to derive them from the history
+    ngrams: Vec<String>,
+    layout: CyclicOption<KeyboardLayout>,
+    layout_progress: LayoutProgress,
+    /// How long typing can stop before the test is paused, if at all
+    idle_pause: Option<Duration>,
+    /// How many times the test was paused for going idle
+    idle_pauses: usize,
+    course: Course,
+    course_progress: CourseProgress,
+    /// The lesson of the course practised in the lesson mode
+    lesson: usize,
+    /// The text given on the command line, typed in the custom text mode
+    custom_text: Option<CustomText>,
+    /// Where typing the custom text stopped in an earlier session, offered to
+    /// resume from when starting
+    resume_position: Option<usize>,
+    keymap: Keymap,
+    persist: bool,
+    history: Vec<TestResult>,
+    last_result: Option<TestResult>,
+    milestones: Vec<String>,
+    /// Today's progress towards the daily goal, updated when the history or
+    /// the goal changes and when a test starts
+    goal_status: Option<GoalStatus>,
+    notice: Option<String>,
     showing_stats: bool,
+    showing_history: bool,
+    /// The test selected in the history browser, newest first
+    history_state: ListState,
+    /// The word selected in the word breakdown of the results
+    words_state: ListState,
+    showing_lessons: bool,
+    /// The lesson selected in the lesson picker
+    lessons_state: ListState,
     showing_size_warning: bool,
+    /// Where the option blocks and buttons were drawn, to act on clicks
+    click_areas: Vec<(Rect, Action)>,
+    /// Where the mouse was last seen, for the tooltip on the WPM chart
+    mouse_position: Option<Position>,
 }
 
 #[derive(Debug)]
 struct TypingEvent {
     duration_since_start: Duration,
+    expected: char,
+    typed: char,
     error: bool,
+    /// The index of the word typed in, if any
+    word: Option<usize>,
 }
 
+/// How often the screen is redrawn while there is no input
+const TICK_RATE: Duration = Duration::from_millis(250);
 const TYPING_AREA_WIDTH: u16 = 72;
-const TYPING_AREA_HEIGHT: u16 = 20;
-const NUMBER_OF_WORDS_KEYBINDING: char = 'w';
-const DIFFICULTY_KEYBINDING: char = 'd';
-const HIGHLIGHT_KEYBINGING: char = 'h';
+/// The title, the stats and the message around the text on the typing screen
+const TYPING_SCREEN_HEIGHT: u16 = 1 + 3 + 3;
+/// The rows of the pause menu, kept free below the message while typing
+const MENU_HEIGHT: u16 = 13;
+/// The rows of the on-screen keyboard and a blank line above them
+const KEYBOARD_HEIGHT: u16 = 5;
+/// How many of the slowest letter pairs are drilled when no n-grams are set
+const NGRAMS_FROM_HISTORY: usize = 6;
+/// The speed and accuracy a learn layout test needs to unlock the next key
+const UNLOCK_WPM: f64 = 20.0;
+const UNLOCK_ACCURACY: f64 = 95.0;
+
+/// The frequencies the text modifiers can be set to
+fn frequencies() -> Vec<Frequency> {
+    [0, 10, 20, 30, 50].into_iter().map(Frequency).collect()
+}
+
+impl Default for App {
+    fn default() -> Self {
+        Self::new()
+    }
+}
 
 impl App {
     pub fn new() -> Self {
-        Self {
+        let mut app = Self {
             lines: vec![],
+            words: vec![],
             stats: Vec::new(),
             cur_line: 0,
             position: 0,
             typed_chars: 0,
+            free_text: String::new(),
             errors: 0,
             pause: false,
+            auto_paused: false,
             quit: false,
             timer: Timer::new(),
             number_of_words: CyclicOption::new(
@@ -66,19 +181,31 @@ impl App {
                     NumberOfWords::TwoHundred,
                     NumberOfWords::FiveHundred,
                 ],
-                NUMBER_OF_WORDS_KEYBINDING,
+                Action::NumberOfWords,
                 "Words",
             ),
-            difficulty: CyclicOption::new(
+            text_mode: CyclicOption::new(
                 vec![
-                    TextDifficulty::Lowercase,
-                    TextDifficulty::Uppercase,
-                    TextDifficulty::Numbers,
-                    TextDifficulty::Symbols,
+                    TextMode::Words,
+                    TextMode::Sentences,
+                    TextMode::NGrams,
+                    TextMode::NGramWords,
+                    TextMode::LearnLayout,
+                    TextMode::Lesson,
+                    TextMode::Zen,
+                    TextMode::Digits,
+                    TextMode::Decimals,
+                    TextMode::PhoneNumbers,
+                    TextMode::Dates,
+                    TextMode::Arithmetic,
                 ],
-                DIFFICULTY_KEYBINDING,
-                "Difficulty",
+                Action::TextMode,
+                "Mode",
             ),
+            capitals: CyclicOption::new(frequencies(), Action::Capitals, "Capitals"),
+            numbers: CyclicOption::new(frequencies(), Action::Numbers, "Numbers"),
+            punctuation: CyclicOption::new(frequencies(), Action::Punctuation, "Punctuation"),
+            brackets: CyclicOption::new(frequencies(), Action::Brackets, "Brackets"),
             highlight: CyclicOption::new(
                 vec![
                     Highlight::Nothing,
@@ -87,23 +214,267 @@ impl App {
                     Highlight::NextWord,
                     Highlight::TwoWords,
                 ],
-                HIGHLIGHT_KEYBINGING,
+                Action::Highlight,
                 "Highlight",
             ),
-            text_generator: TextGenerator::new(NumberOfWords::Ten, TextDifficulty::Lowercase),
+            visible_lines: CyclicOption::new(
+                vec![
+                    VisibleLines::One,
+                    VisibleLines::Three,
+                    VisibleLines::Five,
+                    VisibleLines::Seven,
+                ],
+                Action::VisibleLines,
+                "Lines",
+            ),
+            scrolling: CyclicOption::new(
+                vec![Scrolling::Middle, Scrolling::Top, Scrolling::Tape],
+                Action::Scrolling,
+                "Scrolling",
+            ),
+            caret: CyclicOption::new(
+                vec![
+                    Caret::Bar,
+                    Caret::BlinkingBar,
+                    Caret::Block,
+                    Caret::BlinkingBlock,
+                    Caret::Underline,
+                    Caret::BlinkingUnderline,
+                    Caret::Drawn,
+                ],
+                Action::Caret,
+                "Caret",
+            ),
+            keyboard: CyclicOption::new(
+                vec![OnScreenKeyboard::Hidden, OnScreenKeyboard::Shown],
+                Action::Keyboard,
+                "Keyboard",
+            ),
+            live_stats: CyclicOption::new(
+                vec![LiveStats::Hidden, LiveStats::Shown],
+                Action::LiveStats,
+                "Live",
+            ),
+            results_view: CyclicOption::new(
+                vec![
+                    ResultsView::Chart,
+                    ResultsView::Rolling,
+                    ResultsView::Words,
+                    ResultsView::Fingers,
+                ],
+                Action::ResultsView,
+                "View",
+            ),
+            daily_goal: CyclicOption::new(
+                vec![
+                    DailyGoal::Off,
+                    DailyGoal::Tests(5),
+                    DailyGoal::Tests(10),
+                    DailyGoal::Tests(20),
+                    DailyGoal::Minutes(5),
+                    DailyGoal::Minutes(10),
+                    DailyGoal::Minutes(15),
+                    DailyGoal::Minutes(30),
+                ],
+                Action::DailyGoal,
+                "Goal",
+            ),
+            drill_words: vec![],
+            ngrams: vec![],
+            layout: CyclicOption::new(
+                vec![
+                    KeyboardLayout::Qwerty,
+                    KeyboardLayout::Colemak,
+                    KeyboardLayout::Dvorak,
+                    KeyboardLayout::Workman,
+                ],
+                Action::Layout,
+                "Layout",
+            ),
+            layout_progress: LayoutProgress::default(),
+            idle_pause: None,
+            idle_pauses: 0,
+            course: Course::default(),
+            course_progress: CourseProgress::default(),
+            lesson: 0,
+            custom_text: None,
+            resume_position: None,
+            keymap: Keymap::default(),
+            persist: false,
+            history: vec![],
+            last_result: None,
+            milestones: vec![],
+            goal_status: None,
+            notice: None,
+            text_generator: TextGenerator::new(
+                NumberOfWords::Ten,
+                TextModifiers {
+                    capitals: Frequency::OFF,
+                    numbers: Frequency::OFF,
+                    punctuation: Frequency::OFF,
+                    brackets: Frequency::OFF,
+                },
+                TextMode::Words,
+            ),
             showing_stats: false,
+            showing_history: false,
+            history_state: ListState::default(),
+            words_state: ListState::default(),
+            showing_lessons: false,
+            lessons_state: ListState::default(),
             showing_size_warning: false,
+            click_areas: vec![],
+            mouse_position: None,
+        };
+        app.apply_config(&Config::default());
+        app
+    }
+
+    /// Creates the application with the options and key bindings from
+    /// `config`. Any option changed in the pause menu is written back to disk,
+    /// and finished tests are added to the history.
+    pub fn with_config(config: Config) -> Result<Self> {
+        let mut app = Self::new();
+        app.apply_config(&config);
+        app.keymap = Keymap::new(&config.keys).wrap_err("Invalid key bindings in config.")?;
+        app.persist = true;
+        Ok(app)
+    }
+
+    /// Types the given text instead of generated text
+    pub fn with_text(mut self, text: CustomText) -> Self {
+        self.custom_text = Some(text);
+        self.text_mode.push(TextMode::Custom);
+        self.text_mode.select(&TextMode::Custom);
+        self.text_generator = self.new_text_generator();
+        self
+    }
+
+    fn apply_config(&mut self, config: &Config) {
+        self.number_of_words.select(&config.number_of_words);
+        self.text_mode.select(&config.text_mode);
+        self.capitals.select(&config.capitals);
+        self.numbers.select(&config.numbers);
+        self.punctuation.select(&config.punctuation);
+        self.brackets.select(&config.brackets);
+        self.highlight.select(&config.highlight);
+        self.visible_lines.select(&config.visible_lines);
+        self.scrolling.select(&config.scrolling);
+        self.caret.select(&config.caret);
+        self.keyboard.select(&config.keyboard);
+        self.live_stats.select(&config.live_stats);
+        self.results_view.select(&config.results_view);
+        self.daily_goal.select(&config.daily_goal);
+        self.ngrams = valid_ngrams(&config.ngrams);
+        self.layout.select(&config.layout);
+        self.idle_pause =
+            (config.idle_pause_secs > 0).then(|| Duration::from_secs(config.idle_pause_secs));
+        self.text_generator = self.new_text_generator();
+    }
+
+    fn new_text_generator(&self) -> TextGenerator {
+        let lesson = &self.course.lessons[self.lesson];
+        let modifiers = match self.text_mode.current() {
+            TextMode::Lesson => lesson.modifiers(),
+            _ => self.text_modifiers(),
+        };
+        TextGenerator::new(
+            *self.number_of_words.current(),
+            modifiers,
+            *self.text_mode.current(),
+        )
+        .with_ngrams(self.drilled_ngrams())
+        .with_keys(self.layout_progress.unlocked_keys(*self.layout.current()))
+        .with_lesson(lesson.clone())
+        .with_text(self.custom_text.as_ref().map(CustomText::part))
+        .with_drill_words(self.drill_words.clone())
+    }
+
+    /// The configured n-grams, or else the slowest letter pairs in the history
+    fn drilled_ngrams(&self) -> Vec<String> {
+        if !self.ngrams.is_empty() {
+            return self.ngrams.clone();
+        }
+        let slowest = stats::slowest_transitions(&self.history, NGRAMS_FROM_HISTORY);
+        if slowest.is_empty() {
+            DEFAULT_NGRAMS.iter().map(|n| n.to_string()).collect()
+        } else {
+            slowest
+        }
+    }
+
+    fn text_modifiers(&self) -> TextModifiers {
+        TextModifiers {
+            capitals: *self.capitals.current(),
+            numbers: *self.numbers.current(),
+            punctuation: *self.punctuation.current(),
+            brackets: *self.brackets.current(),
+        }
+    }
+
+    fn config(&self) -> Config {
+        Config {
+            number_of_words: *self.number_of_words.current(),
+            text_mode: match self.text_mode.current() {
+                // The custom text and the drill are only there for this run,
+                // keep the saved mode
+                TextMode::Custom | TextMode::Drill => Config::load().text_mode,
+                mode => *mode,
+            },
+            capitals: *self.capitals.current(),
+            numbers: *self.numbers.current(),
+            punctuation: *self.punctuation.current(),
+            brackets: *self.brackets.current(),
+            highlight: *self.highlight.current(),
+            visible_lines: *self.visible_lines.current(),
+            scrolling: *self.scrolling.current(),
+            caret: *self.caret.current(),
+            keyboard: *self.keyboard.current(),
+            live_stats: *self.live_stats.current(),
+            results_view: *self.results_view.current(),
+            daily_goal: *self.daily_goal.current(),
+            ngrams: self.ngrams.clone(),
+            layout: *self.layout.current(),
+            idle_pause_secs: self.idle_pause.map_or(0, |idle| idle.as_secs()),
+            keys: self.keymap.overrides(),
+        }
+    }
+
+    fn save_config(&self) {
+        if !self.persist {
+            return;
+        }
+        if let Err(err) = self.config().save() {
+            log::error!("Saving config failed: {:?}", err);
         }
     }
 
     /// runs the application's main loop until the user quits
     pub fn run(&mut self, terminal: &mut tui::Tui) -> Result<()> {
+        self.apply_cursor_style()?;
+
+        if self.persist {
+            self.history = load_history().wrap_err("Loading history failed.")?;
+            self.layout_progress = LayoutProgress::load();
+            self.course = Course::load();
+            self.course_progress = CourseProgress::load();
+            self.lesson = self.course_progress.next_lesson(&self.course);
+            self.resume_position = self
+                .custom_text
+                .as_ref()
+                .and_then(CustomText::saved_position);
+        }
+        self.update_goal_status();
+
+        // The n-grams may come from the history
+        self.text_generator = self.new_text_generator();
         self.text_generator
             .load_words()
             .wrap_err("Loading word list failed.")?;
 
         // Generate lines of characters
         self.lines = self.text_generator.generate_lines(TYPING_AREA_WIDTH - 6);
+        self.words = find_words(&self.lines);
 
         while !self.quit {
             terminal.draw(|frame| {
@@ -117,8 +488,36 @@ impl App {
         Ok(())
     }
 
+    fn apply_cursor_style(&self) -> Result<()> {
+        if let Some(style) = self.caret.current().cursor_style() {
+            tui::set_cursor_style(style).wrap_err("Setting cursor style failed.")?;
+        }
+        Ok(())
+    }
+
+    /// The lines of text shown on the typing screen
+    fn text_height(&self) -> u16 {
+        match self.scrolling.current() {
+            Scrolling::Tape => 1,
+            _ => *self.visible_lines.current() as u16,
+        }
+    }
+
+    /// The rows of the on-screen keyboard, if it is shown
+    fn keyboard_height(&self) -> u16 {
+        match self.keyboard.current() {
+            OnScreenKeyboard::Shown => KEYBOARD_HEIGHT,
+            OnScreenKeyboard::Hidden => 0,
+        }
+    }
+
+    /// The smallest terminal height everything on the typing screen fits in
+    fn min_height(&self) -> u16 {
+        TYPING_SCREEN_HEIGHT + MENU_HEIGHT + self.text_height() + self.keyboard_height()
+    }
+
     fn check_size(&mut self, size: Rect) {
-        if size.width < TYPING_AREA_WIDTH || size.height < TYPING_AREA_HEIGHT {
+        if size.width < TYPING_AREA_WIDTH || size.height < self.min_height() {
             self.showing_size_warning = true;
             self.pause();
         } else {
@@ -126,24 +525,300 @@ impl App {
         }
     }
 
-    /// updates the application's state based on user input
+    /// updates the application's state based on user input, waiting at most
+    /// a tick so that the screen is redrawn while nothing happens
     fn handle_events(&mut self) -> Result<()> {
+        let timeout = self
+            .idle_timeout()
+            .map_or(TICK_RATE, |idle| idle.min(TICK_RATE));
+        if !event::poll(timeout)? {
+            if self.idle_timeout().is_some_and(|idle| idle.is_zero()) {
+                self.pause_for_idleness();
+            }
+            return Ok(());
+        }
+
         match event::read()? {
             // it's important to check that the event is a key press event as
             // crossterm also emits key release and repeat events on Windows.
             Event::Key(key_event) if key_event.kind == KeyEventKind::Press => self
                 .handle_key_event(key_event)
                 .wrap_err_with(|| format!("handling key event failed:\n{key_event:#?}")),
+            Event::Mouse(mouse_event) => self
+                .handle_mouse_event(mouse_event)
+                .wrap_err_with(|| format!("handling mouse event failed:\n{mouse_event:#?}")),
+            Event::FocusLost => {
+                if self.typing() {
+                    self.pause_for_focus_loss();
+                }
+                Ok(())
+            }
+            // The screen is redrawn, resized, after every event. A paused test
+            // stays paused when the focus comes back until it is resumed.
+            Event::Resize(..) | Event::FocusGained => Ok(()),
             _ => Ok(()),
         }
     }
 
+    /// Whether a test is being typed, started and not paused or finished
+    fn typing(&self) -> bool {
+        self.timer.running && !self.pause && !self.showing_stats
+    }
+
+    /// How much longer typing can stop before the test is paused, while a
+    /// test is being typed
+    fn idle_timeout(&self) -> Option<Duration> {
+        let idle_pause = self.idle_pause?;
+        let last = self.stats.last()?;
+        if !self.typing() {
+            return None;
+        }
+        let idle = self
+            .timer
+            .elapsed()
+            .saturating_sub(last.duration_since_start);
+        Some(idle_pause.saturating_sub(idle))
+    }
+
+    /// Pauses the test, taking the time since the last keystroke out of it
+    fn pause_for_idleness(&mut self) {
+        let Some(last) = self.stats.last() else {
+            return;
+        };
+        self.pause = true;
+        self.auto_paused = true;
+        self.timer.pause_at(last.duration_since_start);
+        self.idle_pauses += 1;
+        self.notice = Some(format!(
+            "Idle for {}s, paused without counting the idle time",
+            self.idle_pause.map_or(0, |idle| idle.as_secs())
+        ));
+    }
+
+    fn pause_for_focus_loss(&mut self) {
+        self.pause();
+        self.auto_paused = true;
+        self.notice = Some("Paused when the terminal lost focus".to_string());
+    }
+
     fn show_stats(&mut self) {
         self.pause();
         self.showing_stats = true;
+        self.words_state.select(Some(0));
+        self.record_result();
+    }
+
+    fn wpm(&self) -> f64 {
+        let elapsed = self.timer.elapsed();
+        if elapsed.as_millis() <= 2 {
+            0.0
+        } else {
+            (self.typed_chars as f64 / 5.0) / (elapsed.as_secs_f64() / 60.0)
+        }
+    }
+
+    fn accuracy(&self) -> f64 {
+        if self.typed_chars > 0 {
+            (self.typed_chars as f64 - self.errors as f64) / self.typed_chars as f64 * 100.0
+        } else {
+            100.0
+        }
+    }
+
+    fn record_result(&mut self) {
+        let result = TestResult {
+            timestamp: unix_now(),
+            mode: self.text_mode.current().label(),
+            number_of_words: match self.text_mode.current() {
+                TextMode::Zen => self.free_text.split_whitespace().count(),
+                TextMode::Drill => self.words.len(),
+                TextMode::Custom => self
+                    .custom_text
+                    .as_ref()
+                    .map_or(0, |text| text.part().split_whitespace().count()),
+                _ => *self.number_of_words.current() as usize,
+            },
+            difficulty: match self.text_mode.current() {
+                TextMode::LearnLayout => self.layout.current().label(),
+                TextMode::Lesson => self.course.lessons[self.lesson].name.clone(),
+                mode if mode.uses_modifiers() => self.text_modifiers().label(),
+                _ => "none".to_string(),
+            },
+            duration_secs: self.timer.elapsed().as_secs_f64(),
+            wpm: self.wpm(),
+            accuracy: self.accuracy(),
+            correct_chars: self.typed_chars,
+            errors: self.errors,
+            keystrokes: self
+                .stats
+                .iter()
+                .map(|event| Keystroke {
+                    time_ms: event.duration_since_start.as_millis() as u64,
+                    expected: event.expected,
+                    typed: event.typed,
+                    error: event.error,
+                })
+                .collect(),
+            layout: *self.layout.current(),
+            idle_pauses: self.idle_pauses,
+            // Gaps of half the idle time are too long to time a test by
+            long_gaps: self
+                .idle_pause
+                .map_or(0, |idle| utils::long_gaps(&self.stats, idle / 2)),
+        };
+
+        if self.persist {
+            if let Err(err) = append_to_history(&result) {
+                log::error!("Saving result failed: {:?}", err);
+            }
+        }
+        self.history.push(result.clone());
+        self.update_goal_status();
+        self.milestones = stats::milestones(
+            *self.daily_goal.current(),
+            &self.history,
+            stats::day(unix_now()),
+        );
+        if result.idle_pauses > 0 {
+            self.milestones
+                .push("Paused for going idle, not counted as a personal best".to_string());
+        } else if result.long_gaps > 0 {
+            self.milestones
+                .push("Long gaps while typing, not counted as a personal best".to_string());
+        }
+        match self.text_mode.current() {
+            TextMode::LearnLayout => self.unlock_key(&result),
+            TextMode::Lesson => self.check_lesson(&result),
+            TextMode::Custom => self.finish_text_part(),
+            _ => {}
+        }
+        self.last_result = Some(result);
+    }
+
+    /// Moves on to the next part of the custom text, saving the position
+    fn finish_text_part(&mut self) {
+        let Some(text) = &mut self.custom_text else {
+            return;
+        };
+        if text.finish_part() {
+            self.milestones
+                .push(format!("Finished {}, starting over!", text.name));
+        }
+        self.save_text_position();
+    }
+
+    /// Saves how far into the custom text typing got, to resume from there in
+    /// the next session
+    fn save_text_position(&self) {
+        if !self.persist {
+            return;
+        }
+        if let Some(text) = &self.custom_text {
+            if let Err(err) = text.save_position() {
+                log::error!("Saving text position failed: {:?}", err);
+            }
+        }
+    }
+
+    /// Marks the lesson as passed if the test met its targets, moving on to
+    /// the next lesson
+    fn check_lesson(&mut self, result: &TestResult) {
+        let lesson = &self.course.lessons[self.lesson];
+        if !lesson.passed_by(result) {
+            self.milestones.push(format!(
+                "Lesson not passed yet: {:.0}/{:.0} WPM, {:.0}/{:.0}% accuracy",
+                result.wpm, lesson.target_wpm, result.accuracy, lesson.target_accuracy
+            ));
+            return;
+        }
+
+        self.milestones
+            .push(format!("Lesson passed: {}!", lesson.name));
+        self.course_progress.pass(&self.course, self.lesson);
+        if self.persist {
+            if let Err(err) = self.course_progress.save() {
+                log::error!("Saving course progress failed: {:?}", err);
+            }
+        }
+        if self.lesson + 1 < self.course.lessons.len() {
+            self.lesson += 1;
+        }
+    }
+
+    /// Unlocks the next key of the layout being learnt if the test was fast
+    /// and accurate enough
+    fn unlock_key(&mut self, result: &TestResult) {
+        if result.wpm < UNLOCK_WPM || result.accuracy < UNLOCK_ACCURACY {
+            return;
+        }
+        let Some(key) = self.layout_progress.unlock_next(*self.layout.current()) else {
+            return;
+        };
+        self.milestones.push(format!("New key unlocked: {}", key));
+        if self.persist {
+            if let Err(err) = self.layout_progress.save() {
+                log::error!("Saving layout progress failed: {:?}", err);
+            }
+        }
+    }
+
+    /// Writes the last result to JSON and per-keystroke CSV files in the
+    /// current directory
+    fn export_result(&mut self) {
+        let Some(result) = &self.last_result else {
+            return;
+        };
+
+        let name = format!("typirst-{}", result.timestamp);
+        let results = std::slice::from_ref(result);
+        let written = [
+            (ExportFormat::Json, format!("{}.json", name)),
+            (ExportFormat::KeystrokesCsv, format!("{}.csv", name)),
+        ]
+        .into_iter()
+        .try_for_each(|(format, path)| -> Result<()> {
+            fs::write(&path, export::export(results, format)?)?;
+            Ok(())
+        });
+
+        self.notice = Some(match written {
+            Ok(()) => format!("Exported to {name}.json and {name}.csv"),
+            Err(err) => {
+                log::error!("Exporting result failed: {:?}", err);
+                "Export failed, see the log for details".to_string()
+            }
+        });
+    }
+
+    /// Saves a summary card of the result to `typirst-<timestamp>.txt` in
+    /// the current directory and copies it to the clipboard
+    fn share_result(&mut self) {
+        let Some(result) = &self.last_result else {
+            return;
+        };
+
+        let card = export::summary_card(result);
+        let path = format!("typirst-{}.txt", result.timestamp);
+        let shared = fs::write(&path, &card)
+            .wrap_err("Writing the summary card failed.")
+            .and_then(|()| {
+                tui::copy_to_clipboard(&card).wrap_err("Copying the summary card failed.")
+            });
+
+        self.notice = Some(match shared {
+            Ok(()) => format!("Copied the summary card and saved it to {path}"),
+            Err(err) => {
+                log::error!("Sharing result failed: {:?}", err);
+                "Sharing failed, see the log for details".to_string()
+            }
+        });
     }
 
     fn check_character(&mut self, c: char) {
+        let expected = self.lines[self.cur_line][self.position].c;
+        let word = self.words.iter().position(|word| {
+            word.line == self.cur_line && (word.start..word.end).contains(&self.position)
+        });
         let error = self.lines[self.cur_line][self.position].set_typed(c);
         if error {
             self.errors += 1;
@@ -154,12 +829,14 @@ impl App {
 
         if !self.timer.running {
             self.timer.start();
-        } else {
-            self.stats.push(TypingEvent {
-                duration_since_start: self.timer.elapsed(),
-                error,
-            });
         }
+        self.stats.push(TypingEvent {
+            duration_since_start: self.timer.elapsed(),
+            expected,
+            typed: c,
+            error,
+            word,
+        });
 
         if self.position == self.lines[self.cur_line].len() {
             self.position = 0;
@@ -170,40 +847,84 @@ impl App {
         }
     }
 
-    fn handle_key_event(&mut self, key_event: KeyEvent) -> Result<()> {
-        if self.showing_stats {
-            match key_event.code {
-                KeyCode::Char('q') => self.quit(),
-                KeyCode::Char('r') => {
-                    self.unpause();
-                    self.reset();
+    /// Types a character in zen mode, where everything typed counts as correct
+    fn type_freely(&mut self, c: char) {
+        self.free_text.push(c);
+        self.typed_chars += 1;
+
+        if !self.timer.running {
+            self.timer.start();
+        }
+        self.stats.push(TypingEvent {
+            duration_since_start: self.timer.elapsed(),
+            expected: c,
+            typed: c,
+            error: false,
+            word: None,
+        });
+    }
+
+    fn handle_mouse_event(&mut self, mouse_event: MouseEvent) -> Result<()> {
+        let position = Position::new(mouse_event.column, mouse_event.row);
+        match mouse_event.kind {
+            MouseEventKind::Down(MouseButton::Left) => {
+                let clicked = self
+                    .click_areas
+                    .iter()
+                    .find(|(area, _)| area.contains(position))
+                    .map(|(_, action)| *action);
+                if let Some(action) = clicked {
+                    return self.handle_action(action);
                 }
-                _ => {}
             }
-            return Ok(());
+            MouseEventKind::ScrollUp | MouseEventKind::ScrollDown => {
+                let offset = match mouse_event.kind {
+                    MouseEventKind::ScrollUp => -1,
+                    _ => 1,
+                };
+                self.move_selection(offset);
+            }
+            MouseEventKind::Moved => self.mouse_position = Some(position),
+            _ => {}
+        }
+        Ok(())
+    }
+
+    fn handle_key_event(&mut self, key_event: KeyEvent) -> Result<()> {
+        let screen = if self.resume_position.is_some() {
+            Screen::ResumeText
+        } else if self.showing_lessons {
+            Screen::Lessons
+        } else if self.showing_history {
+            Screen::History
+        } else if self.showing_stats {
+            Screen::Results
         } else if self.pause {
+            Screen::Paused
+        } else {
+            Screen::Typing
+        };
+
+        let action = self.keymap.action(screen, &key_event);
+        // Keys typed while the test was paused for them are not menu choices
+        if screen == Screen::Paused && self.auto_paused && action != Some(Action::Resume) {
+            return Ok(());
+        }
+        if let Some(action) = action {
+            return self.handle_action(action);
+        }
+
+        if screen == Screen::Typing && *self.text_mode.current() == TextMode::Zen {
             match key_event.code {
-                KeyCode::Esc => self.unpause(),
-                KeyCode::Char('q') => self.quit(),
-                KeyCode::Char('r') => {
-                    self.unpause();
-                    self.reset();
-                }
-                KeyCode::Char(NUMBER_OF_WORDS_KEYBINDING) => {
-                    self.number_of_words.next();
-                    self.reset();
-                }
-                KeyCode::Char(DIFFICULTY_KEYBINDING) => {
-                    self.difficulty.next();
-                    self.reset();
-                }
-                KeyCode::Char(HIGHLIGHT_KEYBINGING) => {
-                    self.highlight.next();
+                KeyCode::Char(c) => self.type_freely(c),
+                KeyCode::Enter => self.type_freely('\n'),
+                KeyCode::Backspace => {
+                    self.free_text.pop();
+                    self.typed_chars = self.free_text.chars().count();
                 }
                 _ => {}
             }
-            return Ok(());
-        } else {
+        } else if screen == Screen::Typing {
             match key_event.code {
                 KeyCode::Char(c) => {
                     self.check_character(c);
@@ -231,14 +952,211 @@ impl App {
                     }
                     self.lines[self.cur_line][self.position].reset();
                 }
-
-                KeyCode::Esc => self.pause(),
                 _ => {}
             }
         }
         Ok(())
     }
 
+    /// Continues the custom text from where the last session stopped
+    fn resume_text(&mut self) {
+        if let (Some(text), Some(position)) = (&mut self.custom_text, self.resume_position.take()) {
+            text.resume(position);
+        }
+        self.reset();
+    }
+
+    /// Moves the selection in the list on screen: the lessons, the tests in
+    /// the history or the words of the last test
+    fn move_selection(&mut self, offset: isize) {
+        if self.showing_lessons {
+            self.move_lesson_selection(offset);
+        } else if self.showing_history {
+            self.move_history_selection(offset);
+        } else if self.showing_stats && *self.results_view.current() == ResultsView::Words {
+            self.move_word_selection(offset);
+        }
+    }
+
+    fn move_history_selection(&mut self, offset: isize) {
+        let selected = self.history_state.selected().unwrap_or(0) as isize + offset;
+        let last = self.history.len().saturating_sub(1) as isize;
+        self.history_state
+            .select(Some(selected.clamp(0, last) as usize));
+    }
+
+    fn move_word_selection(&mut self, offset: isize) {
+        let selected = self.words_state.selected().unwrap_or(0) as isize + offset;
+        let last = self.word_results().len().saturating_sub(1) as isize;
+        self.words_state
+            .select(Some(selected.clamp(0, last) as usize));
+    }
+
+    /// How each word of the last test was typed
+    fn word_results(&self) -> Vec<WordResult> {
+        word_results(&self.lines, &self.words, &self.stats)
+    }
+
+    /// Starts a test of the slow and mistyped words of the last one
+    fn start_drill(&mut self) {
+        let words = drill_words(&self.word_results());
+        if words.is_empty() {
+            self.notice = Some("There are no slow or mistyped words to drill.".to_string());
+            return;
+        }
+        self.drill_words = words;
+        self.text_mode.push(TextMode::Drill);
+        self.text_mode.select(&TextMode::Drill);
+        self.unpause();
+        self.reset();
+    }
+
+    fn move_lesson_selection(&mut self, offset: isize) {
+        let selected = self.lessons_state.selected().unwrap_or(0) as isize + offset;
+        let last = self.course.lessons.len() as isize - 1;
+        self.lessons_state
+            .select(Some(selected.clamp(0, last) as usize));
+    }
+
+    /// Switches to the lesson mode with the selected lesson, if it is unlocked
+    fn start_selected_lesson(&mut self) {
+        let Some(lesson) = self.lessons_state.selected() else {
+            return;
+        };
+        if !self.course_progress.is_unlocked(&self.course, lesson) {
+            return;
+        }
+        self.lesson = lesson;
+        self.text_mode.select(&TextMode::Lesson);
+        self.save_config();
+        self.showing_lessons = false;
+        self.unpause();
+        self.reset();
+    }
+
+    /// The test selected in the history browser
+    fn selected_history_result(&self) -> Option<&TestResult> {
+        let selected = self.history_state.selected()?;
+        self.history.iter().rev().nth(selected)
+    }
+
+    fn handle_action(&mut self, action: Action) -> Result<()> {
+        match action {
+            Action::Pause => self.pause(),
+            Action::Finish => {
+                // Nothing to show before the first key is typed
+                if *self.text_mode.current() == TextMode::Zen && !self.stats.is_empty() {
+                    self.show_stats();
+                }
+            }
+            Action::Resume => self.unpause(),
+            Action::Quit => self.quit(),
+            Action::Restart | Action::QuickRestart => {
+                self.unpause();
+                self.reset();
+            }
+            Action::Retry => {
+                self.unpause();
+                self.reset_progress();
+            }
+            Action::Export => self.export_result(),
+            Action::Share => self.share_result(),
+            Action::ResultsView => {
+                self.results_view.next();
+                self.save_config();
+            }
+            Action::Lessons => {
+                self.showing_lessons = !self.showing_lessons;
+                self.lessons_state.select(Some(self.lesson));
+            }
+            Action::History => {
+                self.showing_history = !self.showing_history;
+                self.history_state.select(Some(0));
+            }
+            Action::Previous => self.move_selection(-1),
+            Action::Next => self.move_selection(1),
+            Action::Select => self.start_selected_lesson(),
+            Action::Back => {
+                if self.showing_lessons {
+                    self.showing_lessons = false;
+                } else {
+                    self.showing_history = false;
+                }
+            }
+            Action::NumberOfWords => {
+                self.number_of_words.next();
+                self.save_config();
+                self.reset();
+            }
+            Action::TextMode => {
+                self.text_mode.next();
+                self.save_config();
+                self.reset();
+            }
+            Action::Capitals => {
+                self.capitals.next();
+                self.save_config();
+                self.reset();
+            }
+            Action::Numbers => {
+                self.numbers.next();
+                self.save_config();
+                self.reset();
+            }
+            Action::Punctuation => {
+                self.punctuation.next();
+                self.save_config();
+                self.reset();
+            }
+            Action::Brackets => {
+                self.brackets.next();
+                self.save_config();
+                self.reset();
+            }
+            Action::Highlight => {
+                self.highlight.next();
+                self.save_config();
+            }
+            Action::VisibleLines => {
+                self.visible_lines.next();
+                self.save_config();
+            }
+            Action::Scrolling => {
+                self.scrolling.next();
+                self.save_config();
+            }
+            Action::Caret => {
+                self.caret.next();
+                self.save_config();
+                self.apply_cursor_style()?;
+            }
+            Action::Keyboard => {
+                self.keyboard.next();
+                self.save_config();
+            }
+            Action::LiveStats => {
+                self.live_stats.next();
+                self.save_config();
+            }
+            Action::DailyGoal => {
+                self.daily_goal.next();
+                self.update_goal_status();
+                self.save_config();
+            }
+            Action::Drill => self.start_drill(),
+            Action::ResumeText => self.resume_text(),
+            Action::StartOver => self.resume_position = None,
+            Action::Layout => {
+                self.layout.next();
+                self.save_config();
+                if *self.text_mode.current() == TextMode::LearnLayout {
+                    self.reset();
+                }
+            }
+        }
+        Ok(())
+    }
+
     fn pause(&mut self) {
         self.pause = true;
         self.timer.pause();
@@ -246,6 +1164,8 @@ impl App {
 
     fn unpause(&mut self) {
         self.pause = false;
+        self.auto_paused = false;
+        self.notice = None;
         // Only restart the timer if we're not at the beginning of the game
         if self.timer.elapsed().as_secs() > 0 {
             self.timer.start();
@@ -253,25 +1173,146 @@ impl App {
     }
 
     fn quit(&mut self) {
+        // A finished part already moved the position past it
+        if *self.text_mode.current() == TextMode::Custom && !self.showing_stats {
+            let typed = self.lines[..self.cur_line]
+                .iter()
+                .map(Vec::len)
+                .sum::<usize>()
+                + self.position;
+            if let Some(text) = &mut self.custom_text {
+                text.stop_at(typed);
+            }
+        }
+        self.save_text_position();
         self.quit = true;
     }
 
+    fn update_goal_status(&mut self) {
+        let today = stats::day(unix_now());
+        self.goal_status = stats::goal_status(*self.daily_goal.current(), &self.history, today);
+    }
+
+    /// Starts over with new text
     fn reset(&mut self) {
+        self.reset_progress();
+        if let Some(text) = &mut self.custom_text {
+            text.next_part();
+        }
+        self.text_generator = self.new_text_generator();
+        self.text_generator
+            .load_words()
+            .wrap_err("Loading words failed.")
+            .unwrap();
+        self.lines = self.text_generator.generate_lines(TYPING_AREA_WIDTH - 6);
+        self.words = find_words(&self.lines);
+    }
+
+    /// Starts over with the same text
+    fn reset_progress(&mut self) {
         self.cur_line = 0;
         self.position = 0;
         self.typed_chars = 0;
+        self.free_text.clear();
         self.errors = 0;
+        self.idle_pauses = 0;
         self.timer = Timer::new();
         self.stats = Vec::new();
         self.showing_stats = false;
-        self.text_generator = TextGenerator::new(
-            self.number_of_words.current().clone(),
-            self.difficulty.current().clone(),
-        );
-        self.text_generator
-            .load_words()
-            .wrap_err("Loading words failed.")
-            .unwrap();
-        self.lines = self.text_generator.generate_lines(TYPING_AREA_WIDTH - 6);
+        self.notice = None;
+        self.milestones = vec![];
+        // The day may have changed since the last test
+        self.update_goal_status();
+        self.lines.iter_mut().flatten().for_each(Character::reset);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crossterm::event::KeyModifiers;
+    use std::thread::sleep;
+
+    /// An app typing the given line, going idle after `idle_ms`
+    fn app_typing(text: &str, idle_ms: u64) -> App {
+        let mut app = App::new();
+        app.lines = vec![text.chars().map(Character::new).collect()];
+        app.words = find_words(&app.lines);
+        app.idle_pause = Some(Duration::from_millis(idle_ms));
+        app
+    }
+
+    #[test]
+    fn idle_time_is_left_out_and_flagged() {
+        let mut app = app_typing("abc", 40);
+        app.check_character('a');
+        sleep(Duration::from_millis(60));
+        assert_eq!(app.idle_timeout(), Some(Duration::ZERO));
+
+        app.pause_for_idleness();
+        assert!(app.pause);
+        assert!(app.timer.elapsed() < Duration::from_millis(20));
+        app.unpause();
+        app.check_character('b');
+        app.check_character('c');
+
+        let result = app.last_result.as_ref().unwrap();
+        assert_eq!((result.idle_pauses, result.long_gaps), (1, 0));
+        assert!(result.duration_secs < 0.04, "{}", result.duration_secs);
+        assert!(!result.is_valid());
+    }
+
+    #[test]
+    fn keys_after_an_automatic_pause_only_resume() {
+        let key = |code| KeyEvent::new(code, KeyModifiers::NONE);
+        let mut app = app_typing("abc", 40);
+        app.check_character('a');
+        app.pause_for_idleness();
+        // Quit and the options in the pause menu
+        for c in ['q', 'w', 'r'] {
+            app.handle_key_event(key(KeyCode::Char(c))).unwrap();
+        }
+        assert!(!app.quit);
+        assert_eq!(app.position, 1);
+        assert_eq!(app.idle_pauses, 1);
+        app.handle_key_event(key(KeyCode::Esc)).unwrap();
+        assert!(!app.pause);
+
+        app.pause_for_focus_loss();
+        app.handle_key_event(key(KeyCode::Char('q'))).unwrap();
+        assert!(!app.quit);
+        app.handle_key_event(key(KeyCode::Esc)).unwrap();
+        assert!(!app.pause);
+
+        // The menu works as usual when paused by hand
+        app.handle_key_event(key(KeyCode::Esc)).unwrap();
+        app.handle_key_event(key(KeyCode::Char('q'))).unwrap();
+        assert!(app.quit);
+    }
+
+    #[test]
+    fn long_gaps_under_the_idle_time_are_flagged() {
+        let mut app = app_typing("ab", 100);
+        app.check_character('a');
+        sleep(Duration::from_millis(60));
+        assert!(app.idle_timeout().is_some_and(|idle| !idle.is_zero()));
+        app.check_character('b');
+
+        let result = app.last_result.as_ref().unwrap();
+        assert_eq!((result.idle_pauses, result.long_gaps), (0, 1));
+        assert!(!result.is_valid());
+    }
+
+    #[test]
+    fn min_height_fits_the_text_and_the_menu() {
+        let mut app = App::new();
+        app.visible_lines.select(&VisibleLines::Five);
+        assert_eq!(app.min_height(), 25);
+        app.visible_lines.select(&VisibleLines::Seven);
+        assert_eq!(app.min_height(), 27);
+        app.keyboard.select(&OnScreenKeyboard::Shown);
+        assert_eq!(app.min_height(), 27 + KEYBOARD_HEIGHT);
+        app.scrolling.select(&Scrolling::Tape);
+        assert_eq!(app.min_height(), 21 + KEYBOARD_HEIGHT);
     }
 }
@@ -1,20 +1,48 @@
 use color_eyre::Result;
-use dirs::data_dir;
 use simplelog::*;
 use std::fs::{create_dir_all, File};
-use std::path::PathBuf;
+use typirst::config::{self, data_dir};
+use typirst::custom_text::CustomText;
+use typirst::export::export;
+use typirst::history::{load_history, unix_now};
+use typirst::stats::{day, summary};
 use typirst::App;
 
+mod cli;
 mod errors;
+use cli::{Command, ExportArgs, RunArgs, TextSource};
 use typirst::tui;
 
 fn main() -> Result<()> {
-    errors::install_hooks()?;
+    let command = cli::parse_args(std::env::args().skip(1)).unwrap_or_else(|err| {
+        eprintln!("{}\n\n{}", err, cli::USAGE);
+        std::process::exit(2);
+    });
 
+    // Only the TUI needs the terminal to be restored before reporting errors
+    match command {
+        Command::Run(_) => errors::install_hooks()?,
+        _ => color_eyre::install()?,
+    }
+
+    match command {
+        Command::Run(args) => run(args),
+        Command::Export(args) => export_history(args),
+        Command::Stats { trend_len } => {
+            print!("{}", summary(&load_history()?, trend_len, day(unix_now())));
+            Ok(())
+        }
+        Command::Help => {
+            print!("{}", cli::USAGE);
+            Ok(())
+        }
+    }
+}
+
+fn run(args: RunArgs) -> Result<()> {
     // Setup logging
     // Determine the XDG data directory
-    let mut log_file_path = data_dir().unwrap_or_else(|| PathBuf::from("."));
-    log_file_path.push("typirst");
+    let mut log_file_path = data_dir();
     create_dir_all(&log_file_path).unwrap();
     log_file_path.push("app.log");
 
@@ -27,8 +55,34 @@ fn main() -> Result<()> {
     )])
     .unwrap();
 
+    let mut app = App::with_config(config::Config::load())?;
+    // Reading stdin to the end is fine, keys are read from the terminal itself
+    let text = match &args.text {
+        Some(TextSource::Stdin) => Some(CustomText::from_stdin(args.offset, args.length)?),
+        Some(TextSource::File(path)) => {
+            Some(CustomText::from_file(path, args.offset, args.length)?)
+        }
+        None => None,
+    };
+    if let Some(text) = text {
+        app = app.with_text(text);
+    }
     let mut terminal = tui::init()?;
-    App::new().run(&mut terminal)?;
+    app.run(&mut terminal)?;
     tui::restore()?;
     Ok(())
 }
+
+fn export_history(args: ExportArgs) -> Result<()> {
+    let mut results = load_history()?;
+    if args.last {
+        results = results.split_off(results.len().saturating_sub(1));
+    }
+
+    let output = export(&results, args.format)?;
+    match args.output {
+        Some(path) => std::fs::write(path, output)?,
+        None => print!("{}", output),
+    }
+    Ok(())
+}
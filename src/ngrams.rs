@@ -0,0 +1,100 @@
+use rand::seq::SliceRandom;
+use rand::{thread_rng, Rng};
+
+/// Common English letter combinations, drilled when none are configured and
+/// the history is too short to find the slow ones
+pub const DEFAULT_NGRAMS: [&str; 8] = ["th", "he", "in", "er", "an", "ing", "ion", "tion"];
+/// How many times in a row each n-gram is typed in the repeated drill
+const REPETITIONS: usize = 3;
+
+/// The configured n-grams without surrounding whitespace, leaving out blank
+/// ones, which would type as empty words
+pub fn valid_ngrams(ngrams: &[String]) -> Vec<String> {
+    ngrams
+        .iter()
+        .map(|ngram| ngram.trim())
+        .filter(|ngram| !ngram.is_empty())
+        .map(str::to_string)
+        .collect()
+}
+
+/// Generates `count` words that repeat the n-grams, e.g. `ing ing ing th th th`.
+/// Without n-grams there are no words.
+pub fn repeated_ngrams(ngrams: &[String], count: usize) -> Vec<String> {
+    let mut rng = thread_rng();
+    let mut words = Vec::with_capacity(count);
+    while words.len() < count {
+        let Some(ngram) = ngrams.choose(&mut rng) else {
+            break;
+        };
+        let repetitions = REPETITIONS.min(count - words.len());
+        words.extend(std::iter::repeat_n(ngram.clone(), repetitions));
+    }
+    words
+}
+
+/// Picks `count` words from `words` that contain one of the n-grams, spread
+/// evenly over the n-grams. N-grams that no word contains are typed as is.
+pub fn words_with_ngrams(words: &[String], ngrams: &[String], count: usize) -> Vec<String> {
+    if ngrams.is_empty() {
+        return vec![];
+    }
+    let mut rng = thread_rng();
+    let matching: Vec<Vec<&String>> = ngrams
+        .iter()
+        .map(|ngram| {
+            words
+                .iter()
+                .filter(|w| w.contains(ngram.as_str()))
+                .collect()
+        })
+        .collect();
+
+    (0..count)
+        .map(|_| {
+            let idx = rng.gen_range(0..ngrams.len());
+            match matching[idx].choose(&mut rng) {
+                Some(word) => word.to_string(),
+                None => ngrams[idx].clone(),
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ngrams() -> Vec<String> {
+        vec!["th".to_string(), "ing".to_string()]
+    }
+
+    #[test]
+    fn repeats_each_ngram() {
+        let words = repeated_ngrams(&ngrams(), 10);
+        assert_eq!(words.len(), 10);
+        for group in words.chunks(REPETITIONS) {
+            assert!(group.iter().all(|w| w == &group[0]), "{:?}", group);
+        }
+    }
+
+    #[test]
+    fn words_contain_an_ngram() {
+        let list: Vec<String> = ["the", "sing", "apple", "other"]
+            .iter()
+            .map(|w| w.to_string())
+            .collect();
+        let words = words_with_ngrams(&list, &ngrams(), 50);
+        assert_eq!(words.len(), 50);
+        assert!(words.iter().all(|w| w.contains("th") || w.contains("ing")));
+        assert!(!words.contains(&"apple".to_string()));
+    }
+
+    #[test]
+    fn blank_ngrams_are_left_out() {
+        let configured: Vec<String> = ["", " ing ", "  "].iter().map(|w| w.to_string()).collect();
+        assert_eq!(valid_ngrams(&configured), vec!["ing".to_string()]);
+        assert!(repeated_ngrams(&[], 10).is_empty());
+        assert!(words_with_ngrams(&ngrams(), &[], 10).is_empty());
+    }
+}
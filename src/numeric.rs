@@ -0,0 +1,114 @@
+use crate::options::TextMode;
+use rand::seq::SliceRandom;
+use rand::{thread_rng, Rng};
+
+const OPERATORS: [char; 4] = ['+', '-', '*', '/'];
+
+/// Generates `count` numeric items for the numeric text modes, e.g. digit
+/// groups, dates or arithmetic expressions. Some items contain spaces.
+pub fn generate_numeric(mode: TextMode, count: usize) -> Vec<String> {
+    let mut rng = thread_rng();
+    (0..count)
+        .map(|_| match mode {
+            TextMode::Decimals => decimal(&mut rng),
+            TextMode::PhoneNumbers => phone_number(&mut rng),
+            TextMode::Dates => date(&mut rng),
+            TextMode::Arithmetic => arithmetic(&mut rng),
+            _ => {
+                let len = rng.gen_range(3..=6);
+                digits(&mut rng, len)
+            }
+        })
+        .collect()
+}
+
+fn digits(rng: &mut impl Rng, len: usize) -> String {
+    (0..len)
+        .map(|_| char::from_digit(rng.gen_range(0..10), 10).unwrap())
+        .collect()
+}
+
+fn decimal(rng: &mut impl Rng) -> String {
+    let places = rng.gen_range(1..=3);
+    let sign = if rng.gen_bool(0.15) { "-" } else { "" };
+    format!(
+        "{}{}.{}",
+        sign,
+        rng.gen_range(0..10000),
+        digits(rng, places)
+    )
+}
+
+fn phone_number(rng: &mut impl Rng) -> String {
+    match rng.gen_range(0..5) {
+        0 => format!("{}-{}", digits(rng, 3), digits(rng, 4)),
+        1 => format!("({}) {}-{}", digits(rng, 3), digits(rng, 3), digits(rng, 4)),
+        2 => format!(
+            "+{} {} {}",
+            rng.gen_range(1..100),
+            digits(rng, 3),
+            digits(rng, 6)
+        ),
+        3 => format!("{}-{}-{}", digits(rng, 3), digits(rng, 2), digits(rng, 4)),
+        _ => {
+            let letters: String = (0..2).map(|_| rng.gen_range('A'..='Z')).collect();
+            format!("{}-{}", letters, digits(rng, 5))
+        }
+    }
+}
+
+fn date(rng: &mut impl Rng) -> String {
+    let year = rng.gen_range(1950..2050);
+    let month = rng.gen_range(1..=12);
+    let day = rng.gen_range(1..=28);
+    match rng.gen_range(0..4) {
+        0 => format!("{}-{:02}-{:02}", year, month, day),
+        1 => format!("{:02}/{:02}/{}", day, month, year),
+        2 => format!("{:02}.{:02}.{:02}", month, day, year % 100),
+        _ => format!("{:02}:{:02}", rng.gen_range(0..24), rng.gen_range(0..60)),
+    }
+}
+
+fn arithmetic(rng: &mut impl Rng) -> String {
+    let operator = *OPERATORS.choose(rng).unwrap();
+    let (a, b) = (rng.gen_range(1..100), rng.gen_range(1..100));
+    let (a, b, result) = match operator {
+        '+' => (a, b, a + b),
+        '-' => (a.max(b), a.min(b), a.max(b) - a.min(b)),
+        '*' => (a % 13, b % 13, (a % 13) * (b % 13)),
+        // Only exact divisions
+        _ => (a * (b % 12 + 1), b % 12 + 1, a),
+    };
+    format!("{} {} {} = {}", a, operator, b, result)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn arithmetic_is_correct() {
+        for expression in generate_numeric(TextMode::Arithmetic, 200) {
+            let parts: Vec<&str> = expression.split(' ').collect();
+            let a: i64 = parts[0].parse().unwrap();
+            let b: i64 = parts[2].parse().unwrap();
+            let result: i64 = parts[4].parse().unwrap();
+            let expected = match parts[1] {
+                "+" => a + b,
+                "-" => a - b,
+                "*" => a * b,
+                _ => a / b,
+            };
+            assert_eq!(result, expected, "{}", expression);
+            assert!(parts[1] != "/" || a % b == 0, "{}", expression);
+        }
+    }
+
+    #[test]
+    fn digit_groups_only_contain_digits() {
+        for group in generate_numeric(TextMode::Digits, 100) {
+            assert!((3..=6).contains(&group.len()));
+            assert!(group.chars().all(|c| c.is_ascii_digit()));
+        }
+    }
+}
@@ -1,21 +1,36 @@
+use crate::keymap::Action;
+use crossterm::cursor::SetCursorStyle;
+use serde::{Deserialize, Serialize};
+
 #[derive(Debug, Clone)]
 pub struct CyclicOption<T> {
     options: Vec<T>,
-    pub keybinding: char,
+    /// The action that cycles through the options
+    pub action: Action,
     pub label: String,
     current: usize,
 }
 
 impl<T> CyclicOption<T> {
-    pub fn new(options: Vec<T>, c: char, label: &str) -> Self {
+    pub fn new(options: Vec<T>, action: Action, label: &str) -> Self {
         Self {
             options,
-            keybinding: c,
+            action,
             label: label.to_string(),
             current: 0,
         }
     }
 
+    /// Adds an option after the existing ones, unless it is one already
+    pub fn push(&mut self, value: T)
+    where
+        T: PartialEq,
+    {
+        if !self.options.contains(&value) {
+            self.options.push(value);
+        }
+    }
+
     pub fn next(&mut self) {
         self.current = (self.current + 1) % self.options.len();
     }
@@ -24,6 +39,17 @@ impl<T> CyclicOption<T> {
         &self.options[self.current]
     }
 
+    /// Selects the given value if it is one of the options, leaving the
+    /// current selection untouched otherwise.
+    pub fn select(&mut self, value: &T)
+    where
+        T: PartialEq,
+    {
+        if let Some(idx) = self.options.iter().position(|o| o == value) {
+            self.current = idx;
+        }
+    }
+
     pub fn surrounding(&self) -> (&T, &T, &T) {
         let prev = if self.current == 0 {
             self.options.len() - 1
@@ -44,7 +70,8 @@ pub trait Labeled {
     fn label(&self) -> String;
 }
 
-#[derive(Debug, Clone, Copy, PartialEq)]
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
 pub enum NumberOfWords {
     Ten = 10,
     Thirty = 30,
@@ -66,25 +93,60 @@ impl Labeled for NumberOfWords {
     }
 }
 
-#[derive(Debug, Clone, Copy, PartialEq)]
-pub enum TextDifficulty {
-    Lowercase,
-    Numbers,
-    Uppercase,
-    Symbols,
+/// How often a text modifier is applied, as a percentage of the words
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct Frequency(pub u8);
+impl Frequency {
+    pub const OFF: Frequency = Frequency(0);
+
+    /// The number of words out of `total` the modifier applies to, rounded to
+    /// the nearest word
+    pub fn count_of(&self, total: usize) -> usize {
+        (total * self.0 as usize + 50) / 100
+    }
 }
-impl Labeled for TextDifficulty {
+impl Labeled for Frequency {
     fn label(&self) -> String {
-        match self {
-            TextDifficulty::Lowercase => "lowercase".to_string(),
-            TextDifficulty::Numbers => "numbers".to_string(),
-            TextDifficulty::Uppercase => "uppercase".to_string(),
-            TextDifficulty::Symbols => "symbols".to_string(),
+        if *self == Frequency::OFF {
+            "off".to_string()
+        } else {
+            format!("{}%", self.0)
         }
     }
 }
 
+/// Changes applied to the generated words, each independently of the others
 #[derive(Debug, Clone, Copy, PartialEq)]
+pub struct TextModifiers {
+    pub capitals: Frequency,
+    pub numbers: Frequency,
+    pub punctuation: Frequency,
+    pub brackets: Frequency,
+}
+impl Labeled for TextModifiers {
+    fn label(&self) -> String {
+        let enabled: Vec<String> = [
+            ("capitals", self.capitals),
+            ("numbers", self.numbers),
+            ("punctuation", self.punctuation),
+            ("brackets", self.brackets),
+        ]
+        .iter()
+        .filter(|(_, frequency)| *frequency != Frequency::OFF)
+        .map(|(name, frequency)| format!("{} {}", name, frequency.label()))
+        .collect();
+
+        if enabled.is_empty() {
+            "lowercase".to_string()
+        } else {
+            enabled.join(", ")
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
 pub enum Highlight {
     Nothing,
     Character,
@@ -112,3 +174,232 @@ impl Labeled for Highlight {
         }
     }
 }
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum VisibleLines {
+    One = 1,
+    Three = 3,
+    Five = 5,
+    Seven = 7,
+}
+impl Labeled for VisibleLines {
+    fn label(&self) -> String {
+        match self {
+            VisibleLines::One => "1".to_string(),
+            VisibleLines::Three => "3".to_string(),
+            VisibleLines::Five => "5".to_string(),
+            VisibleLines::Seven => "7".to_string(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Scrolling {
+    /// The active line stays in the middle of the visible lines
+    Middle,
+    /// The active line is always the first visible line
+    Top,
+    /// A single line that scrolls horizontally with the cursor
+    Tape,
+}
+impl Labeled for Scrolling {
+    fn label(&self) -> String {
+        match self {
+            Scrolling::Middle => "middle".to_string(),
+            Scrolling::Top => "top".to_string(),
+            Scrolling::Tape => "tape".to_string(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Caret {
+    Bar,
+    BlinkingBar,
+    Block,
+    BlinkingBlock,
+    Underline,
+    BlinkingUnderline,
+    /// Drawn in the text itself instead of using the terminal cursor
+    Drawn,
+}
+impl Caret {
+    /// The terminal cursor style for this caret, or `None` if the caret is
+    /// drawn in the text and the terminal cursor should stay hidden.
+    pub fn cursor_style(&self) -> Option<SetCursorStyle> {
+        match self {
+            Caret::Bar => Some(SetCursorStyle::SteadyBar),
+            Caret::BlinkingBar => Some(SetCursorStyle::BlinkingBar),
+            Caret::Block => Some(SetCursorStyle::SteadyBlock),
+            Caret::BlinkingBlock => Some(SetCursorStyle::BlinkingBlock),
+            Caret::Underline => Some(SetCursorStyle::SteadyUnderScore),
+            Caret::BlinkingUnderline => Some(SetCursorStyle::BlinkingUnderScore),
+            Caret::Drawn => None,
+        }
+    }
+}
+impl Labeled for Caret {
+    fn label(&self) -> String {
+        match self {
+            Caret::Bar => "bar".to_string(),
+            Caret::BlinkingBar => "blinking bar".to_string(),
+            Caret::Block => "block".to_string(),
+            Caret::BlinkingBlock => "blinking block".to_string(),
+            Caret::Underline => "underline".to_string(),
+            Caret::BlinkingUnderline => "blinking underline".to_string(),
+            Caret::Drawn => "drawn".to_string(),
+        }
+    }
+}
+
+/// Whether a keyboard showing the next key is drawn below the text
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum OnScreenKeyboard {
+    Hidden,
+    Shown,
+}
+impl Labeled for OnScreenKeyboard {
+    fn label(&self) -> String {
+        match self {
+            OnScreenKeyboard::Hidden => "hidden".to_string(),
+            OnScreenKeyboard::Shown => "shown".to_string(),
+        }
+    }
+}
+
+/// Whether the typing screen shows the speed and accuracy of the last seconds
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum LiveStats {
+    Hidden,
+    Shown,
+}
+impl Labeled for LiveStats {
+    fn label(&self) -> String {
+        match self {
+            LiveStats::Hidden => "hidden".to_string(),
+            LiveStats::Shown => "shown".to_string(),
+        }
+    }
+}
+
+/// What the results screen shows above the stats
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ResultsView {
+    /// Net and raw WPM since the start of the test, with the errors
+    Chart,
+    /// WPM over the last few seconds, with the errors
+    Rolling,
+    /// Speed and errors per word
+    Words,
+    /// Speed and errors per finger and hand
+    Fingers,
+}
+impl Labeled for ResultsView {
+    fn label(&self) -> String {
+        match self {
+            ResultsView::Chart => "chart".to_string(),
+            ResultsView::Rolling => "rolling chart".to_string(),
+            ResultsView::Words => "words".to_string(),
+            ResultsView::Fingers => "fingers".to_string(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DailyGoal {
+    Off,
+    Tests(usize),
+    Minutes(usize),
+}
+impl Labeled for DailyGoal {
+    fn label(&self) -> String {
+        match self {
+            DailyGoal::Off => "off".to_string(),
+            DailyGoal::Tests(tests) => format!("{} tests", tests),
+            DailyGoal::Minutes(minutes) => format!("{} minutes", minutes),
+        }
+    }
+}
+
+/// The kind of text to type
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TextMode {
+    /// Random words from the word list
+    Words,
+    /// Random words grouped into punctuated pseudo-sentences
+    Sentences,
+    /// The drilled letter combinations, each repeated a few times
+    NGrams,
+    /// Words from the word list that contain the drilled letter combinations
+    NGramWords,
+    /// Words typed with the keys of the keyboard layout unlocked so far
+    LearnLayout,
+    /// The current lesson of the course
+    Lesson,
+    /// No text at all, everything typed is counted until the test is finished
+    Zen,
+    /// The text given on the command line, only available when there is one
+    Custom,
+    /// The slow and mistyped words of an earlier test, only available after
+    /// starting a drill from the results
+    Drill,
+    /// Groups of digits
+    Digits,
+    Decimals,
+    /// Phone numbers and ID-like patterns
+    PhoneNumbers,
+    /// Dates and times
+    Dates,
+    /// Simple arithmetic expressions with their results
+    Arithmetic,
+}
+impl TextMode {
+    /// Whether the mode generates numbers instead of words, in which case the
+    /// text modifiers do not apply
+    pub fn is_numeric(&self) -> bool {
+        matches!(
+            self,
+            TextMode::Digits
+                | TextMode::Decimals
+                | TextMode::PhoneNumbers
+                | TextMode::Dates
+                | TextMode::Arithmetic
+        )
+    }
+
+    /// Whether the capitals, numbers, punctuation and brackets modifiers apply
+    pub fn uses_modifiers(&self) -> bool {
+        matches!(
+            self,
+            TextMode::Words | TextMode::Sentences | TextMode::NGramWords
+        )
+    }
+}
+impl Labeled for TextMode {
+    fn label(&self) -> String {
+        match self {
+            TextMode::Words => "words".to_string(),
+            TextMode::Sentences => "sentences".to_string(),
+            TextMode::NGrams => "n-grams".to_string(),
+            TextMode::NGramWords => "n-gram words".to_string(),
+            TextMode::LearnLayout => "learn layout".to_string(),
+            TextMode::Lesson => "lesson".to_string(),
+            TextMode::Zen => "zen".to_string(),
+            TextMode::Custom => "custom text".to_string(),
+            TextMode::Drill => "word drill".to_string(),
+            TextMode::Digits => "digits".to_string(),
+            TextMode::Decimals => "decimals".to_string(),
+            TextMode::PhoneNumbers => "phone/ids".to_string(),
+            TextMode::Dates => "dates".to_string(),
+            TextMode::Arithmetic => "arithmetic".to_string(),
+        }
+    }
+}
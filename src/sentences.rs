@@ -0,0 +1,125 @@
+use rand::seq::SliceRandom;
+use rand::{thread_rng, Rng};
+
+const MIN_SENTENCE_LEN: usize = 4;
+const MAX_SENTENCE_LEN: usize = 12;
+const CONTRACTIONS: [&str; 16] = [
+    "don't", "doesn't", "didn't", "can't", "won't", "isn't", "aren't", "it's", "that's", "there's",
+    "I'm", "I've", "we're", "they're", "you'll", "let's",
+];
+/// Pairs that surround a phrase inside a sentence
+const PAIRS: [(char, char); 2] = [('"', '"'), ('(', ')')];
+
+/// Groups the words into pseudo-sentences: each one starts with a capital
+/// letter and ends with terminal punctuation, with commas between clauses, the
+/// odd quoted or parenthesised phrase and some contractions mixed in.
+pub fn build_sentences(words: Vec<String>) -> Vec<String> {
+    let mut rng = thread_rng();
+    let mut result = Vec::with_capacity(words.len());
+    let mut words = words.into_iter().peekable();
+
+    while words.peek().is_some() {
+        let len = rng.gen_range(MIN_SENTENCE_LEN..=MAX_SENTENCE_LEN);
+        let mut sentence: Vec<String> = words
+            .by_ref()
+            .take(len)
+            .map(|word| {
+                if rng.gen_bool(0.08) {
+                    CONTRACTIONS.choose(&mut rng).unwrap().to_string()
+                } else if rng.gen_bool(0.03) {
+                    format!("{}'s", word)
+                } else {
+                    word
+                }
+            })
+            .collect();
+        let len = sentence.len();
+
+        // Commas end clauses, which are at least two words long
+        let mut clause_len = 0;
+        for word in sentence.iter_mut().take(len.saturating_sub(2)) {
+            clause_len += 1;
+            if clause_len >= 2 && rng.gen_bool(0.2) {
+                word.push(',');
+                clause_len = 0;
+            }
+        }
+
+        if len >= 5 && rng.gen_bool(0.3) {
+            let start = rng.gen_range(1..len - 1);
+            let end = rng.gen_range(start..(start + 3).min(len));
+            let (open, close) = PAIRS.choose(&mut rng).unwrap();
+            sentence[start].insert(0, *open);
+            // Keep a comma outside of the pair
+            let comma = sentence[end].ends_with(',');
+            if comma {
+                sentence[end].pop();
+            }
+            sentence[end].push(*close);
+            if comma {
+                sentence[end].push(',');
+            }
+        }
+
+        sentence[0] = capitalize_first_letter(&sentence[0]);
+        let terminal = match rng.gen_range(0..100) {
+            0..=74 => '.',
+            75..=89 => '?',
+            _ => '!',
+        };
+        sentence[len - 1].push(terminal);
+
+        result.extend(sentence);
+    }
+
+    result
+}
+
+fn capitalize_first_letter(word: &str) -> String {
+    match word.char_indices().find(|(_, c)| c.is_alphabetic()) {
+        Some((idx, c)) => {
+            format!(
+                "{}{}{}",
+                &word[..idx],
+                c.to_uppercase(),
+                &word[idx + c.len_utf8()..]
+            )
+        }
+        None => word.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sentences() -> Vec<String> {
+        build_sentences(vec!["word".to_string(); 500])
+    }
+
+    #[test]
+    fn keeps_the_number_of_words() {
+        assert_eq!(sentences().len(), 500);
+    }
+
+    #[test]
+    fn sentences_start_with_a_capital_and_end_with_punctuation() {
+        let words = sentences();
+        assert!(words[0].chars().any(|c| c.is_uppercase()));
+        assert!(words.last().unwrap().ends_with(['.', '?', '!']));
+
+        for pair in words.windows(2) {
+            if pair[0].ends_with(['.', '?', '!']) {
+                let first_letter = pair[1].chars().find(|c| c.is_alphabetic()).unwrap();
+                assert!(first_letter.is_uppercase(), "{:?}", pair);
+            }
+        }
+    }
+
+    #[test]
+    fn pairs_are_balanced() {
+        let text = sentences().join(" ");
+        assert_eq!(text.matches('(').count(), text.matches(')').count());
+        assert_eq!(text.matches('"').count() % 2, 0);
+    }
+}
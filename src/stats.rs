@@ -0,0 +1,614 @@
+use crate::history::{Keystroke, TestResult};
+use crate::layout::{Finger, Hand};
+use crate::options::{DailyGoal, Labeled};
+use std::collections::BTreeMap;
+use std::fmt::Write;
+
+const SECONDS_PER_DAY: u64 = 24 * 60 * 60;
+const SPARKLINE_BARS: [char; 8] = ['▁', '▂', '▃', '▄', '▅', '▆', '▇', '█'];
+
+/// The day (in UTC) of a unix timestamp, counted from the epoch
+pub fn day(timestamp: u64) -> u64 {
+    timestamp / SECONDS_PER_DAY
+}
+
+/// Returns the current practice streak and the longest one, in days. The
+/// current streak still counts if the last practice was yesterday, as today
+/// is not over yet.
+pub fn practice_streaks(results: &[TestResult], today: u64) -> (usize, usize) {
+    let mut days: Vec<u64> = results.iter().map(|r| day(r.timestamp)).collect();
+    days.sort_unstable();
+    days.dedup();
+
+    let mut longest = 0;
+    let mut streak = 0;
+    for (idx, day) in days.iter().enumerate() {
+        if idx > 0 && days[idx - 1] + 1 == *day {
+            streak += 1;
+        } else {
+            streak = 1;
+        }
+        longest = longest.max(streak);
+    }
+
+    let current = match days.last() {
+        Some(&last) if last + 1 >= today => streak,
+        _ => 0,
+    };
+    (current, longest)
+}
+
+/// The percentage of correct keystrokes where a digit was expected, and the
+/// number of those keystrokes. `None` if no digits were typed.
+pub fn digit_accuracy<'a>(
+    keystrokes: impl IntoIterator<Item = &'a Keystroke>,
+) -> Option<(f64, usize)> {
+    let (correct, total) = keystrokes
+        .into_iter()
+        .filter(|k| k.expected.is_ascii_digit())
+        .fold((0, 0), |(correct, total), k| {
+            (correct + usize::from(!k.error), total + 1)
+        });
+    (total > 0).then(|| (correct as f64 / total as f64 * 100.0, total))
+}
+
+/// Keystrokes made with a finger or hand
+#[derive(Debug, Clone, Copy, Default)]
+pub struct KeyStats {
+    pub keystrokes: usize,
+    pub errors: usize,
+    /// Time since the previous keystroke, summed over the `timed` keystrokes
+    /// that followed one without a hesitation
+    time_ms: u64,
+    timed: usize,
+}
+
+impl KeyStats {
+    fn add(&mut self, keystroke: &Keystroke, delay: Option<u64>) {
+        self.keystrokes += 1;
+        self.errors += usize::from(keystroke.error);
+        if let Some(delay) = delay.filter(|d| *d <= MAX_TRANSITION_MS) {
+            self.time_ms += delay;
+            self.timed += 1;
+        }
+    }
+
+    /// Average milliseconds per keystroke
+    pub fn ms_per_key(&self) -> Option<f64> {
+        (self.timed > 0).then(|| self.time_ms as f64 / self.timed as f64)
+    }
+
+    /// Percentage of the keystrokes that were errors
+    pub fn error_rate(&self) -> f64 {
+        if self.keystrokes == 0 {
+            0.0
+        } else {
+            self.errors as f64 / self.keystrokes as f64 * 100.0
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct FingerStats {
+    pub fingers: BTreeMap<Finger, KeyStats>,
+    pub hands: BTreeMap<Hand, KeyStats>,
+    /// Consecutive different keys pressed with the same finger
+    pub same_finger_bigrams: usize,
+    /// Consecutive keys pressed with different hands
+    alternations: usize,
+    /// Consecutive keys that are both on the keyboard layout
+    pairs: usize,
+}
+
+impl FingerStats {
+    /// Percentage of consecutive keys that were typed with different hands
+    pub fn alternation_rate(&self) -> Option<f64> {
+        (self.pairs > 0).then(|| self.alternations as f64 / self.pairs as f64 * 100.0)
+    }
+}
+
+/// Aggregates the keystrokes of the results per finger and per hand, using the
+/// layout each test was typed on. Keys outside the main rows are skipped.
+pub fn finger_stats(results: &[TestResult]) -> FingerStats {
+    let mut stats = FingerStats::default();
+    for result in results {
+        let mut previous: Option<(&Keystroke, Option<Finger>)> = None;
+        for keystroke in &result.keystrokes {
+            let finger = result.layout.finger(keystroke.expected);
+            let delay = previous.map(|(p, _)| keystroke.time_ms.saturating_sub(p.time_ms));
+            if let Some(finger) = finger {
+                stats
+                    .fingers
+                    .entry(finger)
+                    .or_default()
+                    .add(keystroke, delay);
+                stats
+                    .hands
+                    .entry(finger.hand())
+                    .or_default()
+                    .add(keystroke, delay);
+            }
+
+            if let (Some((prev, Some(prev_finger))), Some(finger)) = (previous, finger) {
+                stats.pairs += 1;
+                if prev_finger.hand() != finger.hand() {
+                    stats.alternations += 1;
+                } else if prev_finger == finger
+                    && !prev.expected.eq_ignore_ascii_case(&keystroke.expected)
+                {
+                    stats.same_finger_bigrams += 1;
+                }
+            }
+            previous = Some((keystroke, finger));
+        }
+    }
+    stats
+}
+
+/// Letter pairs seen fewer times than this are too noisy to rank
+const MIN_TRANSITIONS: usize = 3;
+/// Longer gaps between keystrokes are hesitations, not transitions
+const MAX_TRANSITION_MS: u64 = 2000;
+
+/// The `count` letter pairs that took the longest on average to type, slowest
+/// first. Only pairs typed correctly are counted.
+pub fn slowest_transitions(results: &[TestResult], count: usize) -> Vec<String> {
+    let mut transitions: BTreeMap<String, (u64, usize)> = BTreeMap::new();
+    for result in results {
+        for pair in result.keystrokes.windows(2) {
+            let (from, to) = (&pair[0], &pair[1]);
+            let delay = to.time_ms.saturating_sub(from.time_ms);
+            if from.error
+                || to.error
+                || !from.expected.is_alphabetic()
+                || !to.expected.is_alphabetic()
+                || delay > MAX_TRANSITION_MS
+            {
+                continue;
+            }
+            let bigram: String = [from.expected, to.expected]
+                .iter()
+                .flat_map(|c| c.to_lowercase())
+                .collect();
+            let (total, seen) = transitions.entry(bigram).or_default();
+            *total += delay;
+            *seen += 1;
+        }
+    }
+
+    let mut averages: Vec<(String, f64)> = transitions
+        .into_iter()
+        .filter(|(_, (_, seen))| *seen >= MIN_TRANSITIONS)
+        .map(|(bigram, (total, seen))| (bigram, total as f64 / seen as f64))
+        .collect();
+    averages.sort_by(|a, b| b.1.total_cmp(&a.1));
+    averages
+        .into_iter()
+        .take(count)
+        .map(|(bigram, _)| bigram)
+        .collect()
+}
+
+const STREAK_MILESTONES: [usize; 7] = [3, 7, 14, 30, 50, 100, 365];
+
+/// Progress towards today's goal, as (done, target) in tests or whole minutes
+pub fn goal_progress(
+    goal: DailyGoal,
+    results: &[TestResult],
+    today: u64,
+) -> Option<(usize, usize)> {
+    let todays_results = results.iter().filter(|r| day(r.timestamp) == today);
+    match goal {
+        DailyGoal::Off => None,
+        DailyGoal::Tests(target) => Some((todays_results.count(), target)),
+        DailyGoal::Minutes(target) => {
+            let secs: f64 = todays_results.map(|r| r.duration_secs).sum();
+            Some(((secs / 60.0) as usize, target))
+        }
+    }
+}
+
+/// Today's progress towards the daily goal and the current practice streak
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct GoalStatus {
+    pub done: usize,
+    pub target: usize,
+    pub streak: usize,
+}
+
+pub fn goal_status(goal: DailyGoal, results: &[TestResult], today: u64) -> Option<GoalStatus> {
+    let (done, target) = goal_progress(goal, results, today)?;
+    let (streak, _) = practice_streaks(results, today);
+    Some(GoalStatus {
+        done,
+        target,
+        streak,
+    })
+}
+
+/// The milestones reached by the last of `results`, to be celebrated on the
+/// results screen
+pub fn milestones(goal: DailyGoal, results: &[TestResult], today: u64) -> Vec<String> {
+    let Some((last, previous)) = results.split_last() else {
+        return vec![];
+    };
+    let mut milestones = vec![];
+
+    let best_before = previous
+        .iter()
+        .filter(|r| r.is_valid() && r.mode == last.mode && r.difficulty == last.difficulty)
+        .map(|r| r.wpm)
+        .fold(None, |best: Option<f64>, wpm| {
+            Some(best.map_or(wpm, |b| b.max(wpm)))
+        });
+    if last.is_valid() && best_before.is_some_and(|best| last.wpm > best) {
+        milestones.push(format!("New personal best: {:.0} WPM!", last.wpm));
+    }
+
+    let goal_met = |results| goal_progress(goal, results, today).is_some_and(|(d, t)| d >= t);
+    if goal_met(results) && !goal_met(previous) {
+        milestones.push("Daily goal reached!".to_string());
+    }
+
+    let first_today = !previous.iter().any(|r| day(r.timestamp) == today);
+    let (streak, _) = practice_streaks(results, today);
+    if first_today && STREAK_MILESTONES.contains(&streak) {
+        milestones.push(format!("{} day practice streak!", streak));
+    }
+
+    milestones
+}
+
+/// Formats a unix timestamp as a UTC date and time, e.g. `2024-03-09 14:05`
+pub fn format_date(timestamp: u64) -> String {
+    // Converts days since the epoch to a civil date, see
+    // http://howardhinnant.github.io/date_algorithms.html#civil_from_days
+    let days = day(timestamp) as i64 + 719_468;
+    let era = days.div_euclid(146_097);
+    let day_of_era = days.rem_euclid(146_097);
+    let year_of_era =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let month_index = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * month_index + 2) / 5 + 1;
+    let month = if month_index < 10 {
+        month_index + 3
+    } else {
+        month_index - 9
+    };
+    let year = year_of_era + era * 400 + i64::from(month <= 2);
+
+    let secs_of_day = timestamp % SECONDS_PER_DAY;
+    format!(
+        "{}-{:02}-{:02} {:02}:{:02}",
+        year,
+        month,
+        day,
+        secs_of_day / 3600,
+        secs_of_day % 3600 / 60
+    )
+}
+
+pub fn format_duration(secs: f64) -> String {
+    let minutes = (secs / 60.0).round() as u64;
+    if secs < 60.0 {
+        format!("{:.0}s", secs)
+    } else if minutes >= 60 {
+        format!("{}h {:02}m", minutes / 60, minutes % 60)
+    } else {
+        format!("{}m", minutes)
+    }
+}
+
+pub fn sparkline(values: &[f64]) -> String {
+    let min = values.iter().cloned().fold(f64::INFINITY, f64::min);
+    let max = values.iter().cloned().fold(f64::NEG_INFINITY, f64::max);
+    values
+        .iter()
+        .map(|v| {
+            let idx = if max > min {
+                ((v - min) / (max - min) * (SPARKLINE_BARS.len() - 1) as f64).round() as usize
+            } else {
+                SPARKLINE_BARS.len() - 1
+            };
+            SPARKLINE_BARS[idx]
+        })
+        .collect()
+}
+
+/// The WPM of each second of a test, counting the correct keystrokes. The
+/// last second is usually cut short and is scaled to its length.
+pub fn wpm_per_second(result: &TestResult) -> Vec<f64> {
+    let Some(last) = result.keystrokes.last() else {
+        return vec![];
+    };
+    let mut chars = vec![0usize; (last.time_ms / 1000) as usize + 1];
+    for keystroke in result.keystrokes.iter().filter(|k| !k.error) {
+        chars[(keystroke.time_ms / 1000) as usize] += 1;
+    }
+    let last_secs = (result.duration_secs - (chars.len() - 1) as f64).clamp(0.1, 1.0);
+    let count = chars.len();
+    chars
+        .into_iter()
+        .enumerate()
+        .map(|(idx, chars)| {
+            let secs = if idx + 1 == count { last_secs } else { 1.0 };
+            chars as f64 / 5.0 / (secs / 60.0)
+        })
+        .collect()
+}
+
+/// How steady the speed was, in percent: 100 minus the coefficient of
+/// variation of the WPM of each second
+pub fn consistency(wpm_per_second: &[f64]) -> f64 {
+    let mean = average(wpm_per_second.iter().copied());
+    if wpm_per_second.len() < 2 || mean == 0.0 {
+        return 100.0;
+    }
+    let variance = average(wpm_per_second.iter().map(|wpm| (wpm - mean).powi(2)));
+    (100.0 - variance.sqrt() / mean * 100.0).clamp(0.0, 100.0)
+}
+
+fn plural(count: usize, noun: &str) -> String {
+    if count == 1 {
+        format!("{} {}", count, noun)
+    } else {
+        format!("{} {}s", count, noun)
+    }
+}
+
+fn average(values: impl Iterator<Item = f64>) -> f64 {
+    let (sum, count) = values.fold((0.0, 0), |(sum, count), v| (sum + v, count + 1));
+    if count == 0 {
+        0.0
+    } else {
+        sum / count as f64
+    }
+}
+
+/// A plain text summary of the history, as printed by `typirst stats`.
+/// `trend_len` is the number of recent tests the accuracy trend covers.
+pub fn summary(results: &[TestResult], trend_len: usize, today: u64) -> String {
+    let mut out = String::new();
+    if results.is_empty() {
+        out.push_str("No tests taken yet.\n");
+        return out;
+    }
+
+    let practice_time: f64 = results.iter().map(|r| r.duration_secs).sum();
+    let (streak, longest_streak) = practice_streaks(results, today);
+    writeln!(out, "Tests taken:      {}", results.len()).unwrap();
+    writeln!(out, "Time practising:  {}", format_duration(practice_time)).unwrap();
+    if let Some((accuracy, digits)) = digit_accuracy(results.iter().flat_map(|r| &r.keystrokes)) {
+        writeln!(
+            out,
+            "Digit accuracy:   {:.1}% over {}",
+            accuracy,
+            plural(digits, "digit")
+        )
+        .unwrap();
+    }
+    writeln!(
+        out,
+        "Practice streak:  {} (longest {}, days start at 00:00 UTC)",
+        plural(streak, "day"),
+        longest_streak
+    )
+    .unwrap();
+
+    let mut modes: BTreeMap<String, Vec<&TestResult>> = BTreeMap::new();
+    for result in results {
+        modes
+            .entry(format!("{} ({})", result.mode, result.difficulty))
+            .or_default()
+            .push(result);
+    }
+    writeln!(out).unwrap();
+    writeln!(
+        out,
+        "{:<28} {:>6} {:>8} {:>9}",
+        "Mode", "Tests", "Avg WPM", "Best WPM"
+    )
+    .unwrap();
+    for (mode, mode_results) in &modes {
+        let best = mode_results
+            .iter()
+            .filter(|r| r.is_valid())
+            .map(|r| r.wpm)
+            .fold(0.0, f64::max);
+        writeln!(
+            out,
+            "{:<28} {:>6} {:>8.1} {:>9.1}",
+            mode,
+            mode_results.len(),
+            average(mode_results.iter().map(|r| r.wpm)),
+            best
+        )
+        .unwrap();
+    }
+
+    let recent_start = results.len().saturating_sub(trend_len);
+    let recent = &results[recent_start..];
+    let previous = &results[recent_start.saturating_sub(trend_len)..recent_start];
+    let accuracies: Vec<f64> = recent.iter().map(|r| r.accuracy).collect();
+    let recent_accuracy = average(accuracies.iter().cloned());
+    writeln!(out).unwrap();
+    write!(
+        out,
+        "Accuracy over the last {}: {} {:.1}%",
+        plural(recent.len(), "test"),
+        sparkline(&accuracies),
+        recent_accuracy
+    )
+    .unwrap();
+    if !previous.is_empty() {
+        let change = recent_accuracy - average(previous.iter().map(|r| r.accuracy));
+        let arrow = if change >= 0.0 { '↑' } else { '↓' };
+        write!(
+            out,
+            " ({} {:.1}% vs the {} before)",
+            arrow,
+            change.abs(),
+            previous.len()
+        )
+        .unwrap();
+    }
+    writeln!(out).unwrap();
+
+    let slowest = slowest_transitions(results, 5);
+    if !slowest.is_empty() {
+        writeln!(out, "Slowest letter pairs: {}", slowest.join(", ")).unwrap();
+    }
+
+    let fingers = finger_stats(results);
+    if !fingers.fingers.is_empty() {
+        writeln!(out).unwrap();
+        writeln!(
+            out,
+            "{:<28} {:>10} {:>8} {:>8}",
+            "Finger", "Keystrokes", "ms/key", "Errors"
+        )
+        .unwrap();
+        let rows = fingers
+            .fingers
+            .iter()
+            .map(|(finger, stats)| (finger.label(), stats))
+            .chain(
+                fingers
+                    .hands
+                    .iter()
+                    .map(|(hand, stats)| (hand.label(), stats)),
+            );
+        for (name, stats) in rows {
+            writeln!(
+                out,
+                "{:<28} {:>10} {:>8.0} {:>7.1}%",
+                name,
+                stats.keystrokes,
+                stats.ms_per_key().unwrap_or(0.0),
+                stats.error_rate()
+            )
+            .unwrap();
+        }
+        writeln!(
+            out,
+            "Same finger bigrams: {}, hand alternation: {:.0}%",
+            fingers.same_finger_bigrams,
+            fingers.alternation_rate().unwrap_or(0.0)
+        )
+        .unwrap();
+    }
+
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::layout::KeyboardLayout;
+
+    fn result_typing(text: &str) -> TestResult {
+        TestResult {
+            timestamp: 0,
+            mode: "words".to_string(),
+            number_of_words: 1,
+            difficulty: "lowercase".to_string(),
+            duration_secs: 1.0,
+            wpm: 0.0,
+            accuracy: 100.0,
+            correct_chars: text.len(),
+            errors: 0,
+            keystrokes: text
+                .chars()
+                .enumerate()
+                .map(|(idx, c)| Keystroke {
+                    time_ms: idx as u64 * 100,
+                    expected: c,
+                    typed: c,
+                    error: false,
+                })
+                .collect(),
+            layout: KeyboardLayout::Qwerty,
+            idle_pauses: 0,
+            long_gaps: 0,
+        }
+    }
+
+    #[test]
+    fn measures_consistency_per_second() {
+        let mut result = result_typing("abcdefghij");
+        result.duration_secs = 1.5;
+        for (idx, keystroke) in result.keystrokes.iter_mut().enumerate() {
+            keystroke.time_ms = idx as u64 * 125;
+        }
+        // 8 characters in the first second and 2 in the last half second
+        let wpm = wpm_per_second(&result);
+        assert_eq!(wpm, vec![96.0, 48.0]);
+        assert_eq!(consistency(&wpm).round(), 67.0);
+        assert_eq!(consistency(&[60.0, 60.0]), 100.0);
+    }
+
+    #[test]
+    fn summarises_a_small_history() {
+        let results: Vec<TestResult> = [
+            // (day, mode, wpm, accuracy, duration, idle pauses)
+            (0, "words", 40.0, 90.0, 30.0, 0),
+            (1, "words", 60.0, 100.0, 30.0, 0),
+            (2, "sentences", 50.0, 95.0, 60.0, 0),
+            (2, "words", 80.0, 97.0, 60.0, 1),
+        ]
+        .into_iter()
+        .map(|(day, mode, wpm, accuracy, duration_secs, idle_pauses)| {
+            let mut result = result_typing("ab");
+            result.timestamp = day * SECONDS_PER_DAY;
+            result.mode = mode.to_string();
+            result.wpm = wpm;
+            result.accuracy = accuracy;
+            result.duration_secs = duration_secs;
+            result.idle_pauses = idle_pauses;
+            result
+        })
+        .collect();
+
+        let text = summary(&results, 2, 2);
+        let lines: Vec<&str> = text.lines().collect();
+        assert_eq!(lines[0], "Tests taken:      4");
+        assert_eq!(lines[1], "Time practising:  3m");
+        assert_eq!(
+            lines[2],
+            "Practice streak:  3 days (longest 3, days start at 00:00 UTC)"
+        );
+        // The idle test does not count as the best of its mode
+        assert_eq!(
+            lines[5],
+            "sentences (lowercase)             1     50.0      50.0"
+        );
+        assert_eq!(
+            lines[6],
+            "words (lowercase)                 3     60.0      60.0"
+        );
+        assert_eq!(
+            lines[8],
+            "Accuracy over the last 2 tests: ▁█ 96.0% (↑ 1.0% vs the 2 before)"
+        );
+        assert_eq!(summary(&[], 2, 2), "No tests taken yet.\n");
+    }
+
+    #[test]
+    fn formats_dates_in_utc() {
+        assert_eq!(format_date(0), "1970-01-01 00:00");
+        assert_eq!(format_date(951_825_600), "2000-02-29 12:00");
+        assert_eq!(format_date(1_735_689_599), "2024-12-31 23:59");
+    }
+
+    #[test]
+    fn counts_same_finger_bigrams_and_alternation() {
+        // "ed" is typed with the left middle finger, "dj" and "ja" alternate hands
+        let stats = finger_stats(&[result_typing("edja")]);
+        assert_eq!(stats.same_finger_bigrams, 1);
+        assert_eq!(stats.alternation_rate().map(f64::round), Some(67.0));
+        assert_eq!(stats.fingers[&Finger::LeftMiddle].keystrokes, 2);
+        assert_eq!(stats.hands[&Hand::Left].keystrokes, 3);
+        assert_eq!(stats.hands[&Hand::Right].ms_per_key(), Some(100.0));
+    }
+}
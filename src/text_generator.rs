@@ -1,7 +1,12 @@
-use crate::options::{self, NumberOfWords};
-use crate::utils::{add_symbols, capitalize_20_percent, convert_15_percent_to_numbers};
+use crate::course::Lesson;
+use crate::layout::words_with_keys;
+use crate::ngrams::{repeated_ngrams, words_with_ngrams};
+use crate::numeric::generate_numeric;
+use crate::options::{NumberOfWords, TextMode, TextModifiers};
+use crate::sentences::build_sentences;
+use crate::utils::{add_brackets, add_punctuation, capitalize, convert_to_numbers};
+use crate::word_stats::drill_text;
 use color_eyre::Result;
-use options::TextDifficulty;
 use rand::seq::SliceRandom;
 
 #[derive(Debug, Default, PartialEq)]
@@ -46,22 +51,100 @@ impl Character {
     }
 }
 
+/// A word of the text, the characters `start..end` of a line
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Word {
+    pub line: usize,
+    pub start: usize,
+    pub end: usize,
+}
+
+/// Finds the words of the lines, the runs of characters between whitespace
+pub fn find_words(lines: &[Vec<Character>]) -> Vec<Word> {
+    let mut words = vec![];
+    for (line_idx, line) in lines.iter().enumerate() {
+        let mut start = None;
+        for (idx, c) in line.iter().enumerate() {
+            match (start, c.c.is_whitespace()) {
+                (None, false) => start = Some(idx),
+                (Some(word_start), true) => {
+                    words.push(Word {
+                        line: line_idx,
+                        start: word_start,
+                        end: idx,
+                    });
+                    start = None;
+                }
+                _ => {}
+            }
+        }
+        if let Some(word_start) = start {
+            words.push(Word {
+                line: line_idx,
+                start: word_start,
+                end: line.len(),
+            });
+        }
+    }
+    words
+}
+
 #[derive(Debug)]
 pub struct TextGenerator {
     words: Vec<String>,
-    difficulty: TextDifficulty,
+    modifiers: TextModifiers,
     number_of_words: NumberOfWords,
+    mode: TextMode,
+    /// The letter combinations drilled in the n-gram modes
+    ngrams: Vec<String>,
+    /// The keys that may be used in the learn layout mode
+    keys: Vec<char>,
+    lesson: Option<Lesson>,
+    /// The text typed in the custom text mode
+    text: Option<String>,
+    drill_words: Vec<String>,
 }
 
 impl TextGenerator {
-    pub fn new(number_of_words: NumberOfWords, difficulty: TextDifficulty) -> Self {
+    pub fn new(number_of_words: NumberOfWords, modifiers: TextModifiers, mode: TextMode) -> Self {
         Self {
             words: vec![],
             number_of_words,
-            difficulty,
+            modifiers,
+            mode,
+            ngrams: vec![],
+            keys: vec![],
+            lesson: None,
+            text: None,
+            drill_words: vec![],
         }
     }
 
+    pub fn with_ngrams(mut self, ngrams: Vec<String>) -> Self {
+        self.ngrams = ngrams;
+        self
+    }
+
+    pub fn with_keys(mut self, keys: Vec<char>) -> Self {
+        self.keys = keys;
+        self
+    }
+
+    pub fn with_lesson(mut self, lesson: Lesson) -> Self {
+        self.lesson = Some(lesson);
+        self
+    }
+
+    pub fn with_text(mut self, text: Option<String>) -> Self {
+        self.text = text;
+        self
+    }
+
+    pub fn with_drill_words(mut self, words: Vec<String>) -> Self {
+        self.drill_words = words;
+        self
+    }
+
     pub fn load_words(&mut self) -> Result<()> {
         let text = include_str!("../assets/words.txt");
 
@@ -74,7 +157,20 @@ impl TextGenerator {
     }
 
     pub fn generate_lines(&self, max_len: u16) -> Vec<Vec<Character>> {
-        let text = self.select_words();
+        let count = self.number_of_words as usize;
+        let text = match self.mode {
+            TextMode::Zen => return vec![],
+            TextMode::Custom => self.text.clone().unwrap_or_default(),
+            TextMode::Drill => drill_text(&self.drill_words).join(" "),
+            TextMode::NGrams => repeated_ngrams(&self.ngrams, count).join(" "),
+            TextMode::NGramWords => {
+                self.apply_modifiers(words_with_ngrams(&self.words, &self.ngrams, count).join(" "))
+            }
+            TextMode::LearnLayout => words_with_keys(&self.words, &self.keys, count).join(" "),
+            TextMode::Lesson => self.apply_modifiers(self.lesson_words(count).join(" ")),
+            mode if mode.is_numeric() => generate_numeric(mode, count).join(" "),
+            _ => self.apply_modifiers(self.select_words()),
+        };
         self.split_string(text, max_len)
     }
 
@@ -86,24 +182,41 @@ impl TextGenerator {
             .join(" ")
     }
 
-    fn apply_difficulty(&self, input: String) -> String {
+    /// Words from the lesson's own list or the word list, limited to the
+    /// lesson's keys if it has any
+    fn lesson_words(&self, count: usize) -> Vec<String> {
+        let lesson = self.lesson.as_ref();
+        let words = lesson
+            .and_then(|l| l.words.as_ref())
+            .filter(|words| !words.is_empty())
+            .unwrap_or(&self.words);
+        match lesson.and_then(|l| l.keys.as_ref()) {
+            Some(keys) => words_with_keys(words, &keys.chars().collect::<Vec<char>>(), count),
+            None => (0..count)
+                .map(|_| words.choose(&mut rand::thread_rng()).unwrap().clone())
+                .collect(),
+        }
+    }
+
+    fn apply_modifiers(&self, input: String) -> String {
         let mut words: Vec<String> = input.split_whitespace().map(|s| s.to_string()).collect();
 
-        words = match self.difficulty {
-            TextDifficulty::Lowercase => words,
-            TextDifficulty::Uppercase => capitalize_20_percent(words),
-            TextDifficulty::Numbers => convert_15_percent_to_numbers(capitalize_20_percent(words)),
-            TextDifficulty::Symbols => {
-                add_symbols(convert_15_percent_to_numbers(capitalize_20_percent(words)))
+        words = convert_to_numbers(words, self.modifiers.numbers);
+        match self.mode {
+            // Sentences come with their own capitals and punctuation
+            TextMode::Sentences => words = build_sentences(words),
+            _ => {
+                words = capitalize(words, self.modifiers.capitals);
+                // Punctuation goes after the closing bracket, as in `(word),`
+                words = add_brackets(words, self.modifiers.brackets);
+                words = add_punctuation(words, self.modifiers.punctuation);
             }
-        };
+        }
 
         words.join(" ")
     }
 
     fn split_string(&self, input: String, max_len: u16) -> Vec<Vec<Character>> {
-        let input = self.apply_difficulty(input);
-
         let mut result = Vec::new();
         let mut start = 0;
         let mut last_space = 0;
@@ -158,3 +271,36 @@ impl TextGenerator {
         result
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::options::Frequency;
+
+    #[test]
+    fn modifiers_combine_with_numbers_and_brackets() {
+        let modifiers = TextModifiers {
+            capitals: Frequency(50),
+            numbers: Frequency(40),
+            punctuation: Frequency(100),
+            brackets: Frequency(100),
+        };
+        let generator = TextGenerator::new(NumberOfWords::Ten, modifiers, TextMode::Words);
+        let text = generator.apply_modifiers(vec!["word"; 100].join(" "));
+        let words: Vec<&str> = text.split(' ').collect();
+        assert_eq!(words.len(), 100);
+
+        for word in &words {
+            let chars: Vec<char> = word.chars().collect();
+            let close = chars[chars.len() - 2];
+            assert!(")]}>\"'".contains(close), "{} is not wrapped", word);
+            assert!(!chars[chars.len() - 1].is_alphanumeric(), "{}", word);
+        }
+        // Half of all the words get a capital, none of them wasted on numbers
+        let capitalized = words
+            .iter()
+            .filter(|word| word.chars().any(char::is_uppercase))
+            .count();
+        assert_eq!(capitalized, 50);
+    }
+}
@@ -31,6 +31,14 @@ impl Timer {
         self.running = false;
     }
 
+    /// Pauses the timer as if it had stopped when it read `elapsed`, leaving
+    /// out the time since
+    pub fn pause_at(&mut self, elapsed: Duration) {
+        self.total_duration = elapsed.min(self.elapsed());
+        self.start_time = None;
+        self.running = false;
+    }
+
     pub fn elapsed(&self) -> Duration {
         let extra_duration = self
             .start_time
@@ -1,6 +1,13 @@
-use std::io::{self, stdout, Stdout};
+use std::io::{self, stdout, Stdout, Write};
 
-use crossterm::{cursor::SetCursorStyle, execute, terminal::*};
+use crate::utils::base64;
+
+use crossterm::{
+    cursor::{SetCursorStyle, Show},
+    event::{DisableFocusChange, DisableMouseCapture, EnableFocusChange, EnableMouseCapture},
+    execute,
+    terminal::*,
+};
 use ratatui::prelude::*;
 
 /// A type alias for the terminal type used in this application
@@ -8,7 +15,12 @@ pub type Tui = Terminal<CrosstermBackend<Stdout>>;
 
 /// Initialize the terminal
 pub fn init() -> io::Result<Tui> {
-    execute!(stdout(), EnterAlternateScreen, SetCursorStyle::SteadyBar,)?;
+    execute!(
+        stdout(),
+        EnterAlternateScreen,
+        EnableMouseCapture,
+        EnableFocusChange
+    )?;
     enable_raw_mode()?;
     Terminal::new(CrosstermBackend::new(stdout()))
 }
@@ -18,8 +30,24 @@ pub fn restore() -> io::Result<()> {
     execute!(
         stdout(),
         SetCursorStyle::DefaultUserShape,
+        Show,
+        DisableMouseCapture,
+        DisableFocusChange,
         LeaveAlternateScreen
     )?;
     disable_raw_mode()?;
     Ok(())
 }
+
+/// Change the shape of the terminal cursor
+pub fn set_cursor_style(style: SetCursorStyle) -> io::Result<()> {
+    execute!(stdout(), style)
+}
+
+/// Copy text to the clipboard of the terminal with an OSC 52 sequence. Not
+/// every terminal supports it, and there is no way to tell if it worked.
+pub fn copy_to_clipboard(text: &str) -> io::Result<()> {
+    let mut stdout = stdout();
+    write!(stdout, "\x1b]52;c;{}\x07", base64(text.as_bytes()))?;
+    stdout.flush()
+}
@@ -1,3 +1,4 @@
+use crate::keymap::Action;
 use crate::options::{CyclicOption, Labeled};
 use crate::App;
 use ratatui::style::palette::tailwind::{EMERALD, RED, SLATE};
@@ -16,65 +17,90 @@ pub struct Colors {
     pub incorrect: Color,
 }
 
-pub fn get_colors(cur_line: usize, line_idx: usize, c: char) -> Colors {
-    if line_idx == cur_line && c != ' ' {
+/// Returns the colors of a character `distance` lines away from the active
+/// line, dimming them the further away it is.
+pub fn get_colors(distance: usize, c: char) -> Colors {
+    if distance == 0 && c != ' ' {
         Colors {
             untyped: SLATE.c50,
             correct: EMERALD.c400,
             incorrect: RED.c400,
         }
-    } else if (line_idx as isize - cur_line as isize).abs() <= 1 {
+    } else if distance <= 1 {
         Colors {
             untyped: SLATE.c400,
             correct: EMERALD.c700,
             incorrect: RED.c800,
         }
-    } else {
+    } else if distance <= 2 {
         Colors {
             untyped: SLATE.c500,
             correct: EMERALD.c800,
             incorrect: RED.c900,
         }
+    } else {
+        Colors {
+            untyped: SLATE.c600,
+            correct: EMERALD.c900,
+            incorrect: RED.c950,
+        }
     }
 }
 
-pub fn render_options_keybind_block(f: &mut Frame, layout: Rect, keybinding: &str, label: &str) {
+/// Renders a button for the action, which can be clicked as well
+pub fn render_options_keybind_block(
+    f: &mut Frame,
+    layout: Rect,
+    action: Action,
+    label: &str,
+    app: &mut App,
+) {
+    app.click_areas.push((layout, action));
     let block = Block::default();
-    let menu_text = Paragraph::new(vec![Line::from(format!(" {} ({}) ", label, keybinding))
-        .centered()
-        .style(Style::default().bold().bg(SLATE.c800).fg(SLATE.c100))])
+    let menu_text = Paragraph::new(vec![Line::from(format!(
+        " {} ({}) ",
+        label,
+        app.keymap.key(action)
+    ))
+    .centered()
+    .style(Style::default().bold().bg(SLATE.c800).fg(SLATE.c100))])
     .block(block);
 
     f.render_widget(menu_text, layout);
 }
 
+/// Renders an option with its previous and next values. Clicking it cycles
+/// the option like its key does.
 pub fn render_cyclic_options_block<T: Labeled>(
     f: &mut Frame,
     layout: Rect,
     option_container: CyclicOption<T>,
+    app: &mut App,
 ) {
+    app.click_areas.push((layout, option_container.action));
     let mut visible_options = vec![];
     let options = option_container.surrounding();
     visible_options.push(
         Line::from(format!(
             " {} ({}) ",
-            option_container.label, option_container.keybinding
+            option_container.label,
+            app.keymap.key(option_container.action)
         ))
         .centered()
         .style(Style::default().bold().bg(SLATE.c800).fg(SLATE.c100)),
     );
     visible_options.push(
-        Line::from(format!("{}", options.0.label()))
+        Line::from(options.0.label())
             .centered()
             .style(Style::default().fg(SLATE.c500)),
     );
     visible_options.push(
-        Line::from(format!("{}", options.1.label()))
+        Line::from(options.1.label())
             .centered()
             .style(Style::default().bold().fg(SLATE.c300)),
     );
     visible_options.push(
-        Line::from(format!("{}", options.2.label()))
+        Line::from(options.2.label())
             .centered()
             .style(Style::default().fg(SLATE.c500)),
     );
@@ -98,13 +124,7 @@ pub fn render_stats_block(f: &mut Frame, layout: Rect, title: &str, value: Strin
 }
 
 pub fn render_average_wpm(f: &mut Frame, layout: Rect, app: &App) {
-    let elapsed = app.timer.elapsed();
-    let wpm: f64 = if elapsed.as_millis() <= 2 {
-        0.0
-    } else {
-        let elapsed = elapsed.as_secs_f64() / 60.0;
-        (app.typed_chars as f64 / 5.0) / elapsed
-    };
+    let wpm = app.wpm();
 
     let wpm_string = if wpm == 0.0 {
         "-".to_string()
@@ -116,13 +136,7 @@ pub fn render_average_wpm(f: &mut Frame, layout: Rect, app: &App) {
 }
 
 pub fn render_accuracy(f: &mut Frame, layout: Rect, app: &App) {
-    let accuracy = if app.typed_chars > 0 {
-        (app.typed_chars as f64 - app.errors as f64) / app.typed_chars as f64 * 100.0
-    } else {
-        100.0
-    };
-
-    render_stats_block(f, layout, " Accuracy ", format!("{:.0}%", accuracy));
+    render_stats_block(f, layout, " Accuracy ", format!("{:.0}%", app.accuracy()));
 }
 
 pub fn render_errors(f: &mut Frame, layout: Rect, app: &App) {
@@ -0,0 +1,66 @@
+use crate::options::Labeled;
+use crate::stats::{FingerStats, KeyStats};
+use ratatui::style::palette::tailwind::SLATE;
+use ratatui::{
+    prelude::*,
+    style::Style,
+    text::{Line, Span},
+    widgets::Paragraph,
+    Frame,
+};
+
+/// Error rates above this are shown in red
+const HIGH_ERROR_RATE: f64 = 10.0;
+
+fn stats_line(name: String, stats: &KeyStats) -> Line<'static> {
+    let ms_per_key = stats
+        .ms_per_key()
+        .map_or("-".to_string(), |ms| format!("{:.0}", ms));
+    let error_rate = Span::from(format!("{:>7.1}%", stats.error_rate()));
+    Line::from(vec![
+        Span::from(format!(
+            "{:<14}{:>6}{:>8}",
+            name, stats.keystrokes, ms_per_key
+        )),
+        if stats.error_rate() > HIGH_ERROR_RATE {
+            error_rate.red()
+        } else {
+            error_rate
+        },
+    ])
+}
+
+/// Renders speed and errors per finger and hand, the number of same finger
+/// bigrams and the hand alternation rate
+pub fn render_finger_stats(f: &mut Frame, layout: Rect, stats: &FingerStats) {
+    if stats.fingers.is_empty() {
+        let message = Paragraph::new("No keys of the keyboard layout were typed.").centered();
+        f.render_widget(message, layout);
+        return;
+    }
+
+    let mut lines = vec![Line::from(format!(
+        "{:<14}{:>6}{:>8}{:>8}",
+        "Finger", "Keys", "ms/key", "Errors"
+    ))
+    .style(Style::default().bold().white())];
+    lines.extend(
+        stats
+            .fingers
+            .iter()
+            .map(|(finger, finger_stats)| stats_line(finger.label(), finger_stats)),
+    );
+    lines.extend(stats.hands.iter().map(|(hand, hand_stats)| {
+        stats_line(hand.label(), hand_stats).style(Style::default().bold())
+    }));
+    lines.push(
+        Line::from(format!(
+            "Same finger bigrams: {}  Alternation: {:.0}%",
+            stats.same_finger_bigrams,
+            stats.alternation_rate().unwrap_or(0.0)
+        ))
+        .style(Style::default().fg(SLATE.c400)),
+    );
+
+    f.render_widget(Paragraph::new(lines).centered(), layout);
+}
@@ -1,13 +1,20 @@
+use crate::keymap::Action;
+use crate::options::ResultsView;
+use crate::stats::{digit_accuracy, finger_stats};
 use crate::ui::common::{
     render_accuracy, render_average_wpm, render_errors, render_options_keybind_block,
+    render_stats_block,
 };
-use crate::utils::calculate_wpm_and_errors_datasets;
+use crate::ui::fingers::render_finger_stats;
+use crate::ui::words::render_word_results;
+use crate::utils::{wpm_at, wpm_series, DataPoints, ROLLING_WINDOW_SECS};
 use crate::App;
+use ratatui::style::palette::tailwind::{SLATE, YELLOW};
 use ratatui::{
-    layout::{Constraint, Direction, Layout},
+    layout::{Constraint, Direction, Layout, Position},
     prelude::*,
     style::Style,
-    widgets::{block::Title, Axis, Block, Chart, Dataset, GraphType},
+    widgets::{block::Title, Axis, Block, Chart, Clear, Dataset, GraphType, Paragraph},
     Frame,
 };
 
@@ -19,6 +26,7 @@ pub fn render_graph(f: &mut Frame, app: &mut App) {
             Constraint::Percentage(50),
             Constraint::Length(3),
             Constraint::Fill(1),
+            Constraint::Length(1),
             Constraint::Length(4),
         ])
         .split(f.size());
@@ -32,83 +40,284 @@ pub fn render_graph(f: &mut Frame, app: &mut App) {
         ])
         .split(vertical_layout[1]);
 
-    let (wpm_data, error_data) = calculate_wpm_and_errors_datasets(&app.stats);
-    let datasets = vec![
-        // Line chart
+    match app.results_view.current() {
+        view @ (ResultsView::Chart | ResultsView::Rolling) => {
+            render_wpm_chart(f, graph_layout[1], app, *view)
+        }
+        ResultsView::Words => render_word_results(f, graph_layout[1], app),
+        ResultsView::Fingers => {
+            if let Some(result) = &app.last_result {
+                let stats = finger_stats(std::slice::from_ref(result));
+                render_finger_stats(f, graph_layout[1], &stats);
+            }
+        }
+    }
+
+    // Stats layout
+    let digit_accuracy = app
+        .last_result
+        .as_ref()
+        .and_then(|result| digit_accuracy(&result.keystrokes));
+    let mut stats_constraints = vec![
+        Constraint::Fill(2),
+        Constraint::Length(18),
+        Constraint::Fill(1),
+        Constraint::Length(18),
+        Constraint::Fill(1),
+        Constraint::Length(18),
+    ];
+    if digit_accuracy.is_some() {
+        stats_constraints.extend([Constraint::Fill(1), Constraint::Length(18)]);
+    }
+    stats_constraints.push(Constraint::Fill(2));
+    let stats_layout = Layout::default()
+        .direction(Direction::Horizontal)
+        .constraints(stats_constraints)
+        .split(vertical_layout[2]);
+    render_average_wpm(f, stats_layout[1], app);
+    render_errors(f, stats_layout[3], app);
+    render_accuracy(f, stats_layout[5], app);
+    if let Some((accuracy, _)) = digit_accuracy {
+        render_stats_block(
+            f,
+            stats_layout[7],
+            " Digit accuracy ",
+            format!("{:.0}%", accuracy),
+        );
+    }
+
+    if let Some(notice) = &app.notice {
+        f.render_widget(
+            Line::from(notice.as_str())
+                .centered()
+                .style(Style::default().gray()),
+            vertical_layout[4],
+        );
+    } else if !app.milestones.is_empty() {
+        f.render_widget(
+            Line::from(app.milestones.join("  "))
+                .centered()
+                .style(Style::default().yellow().bold()),
+            vertical_layout[4],
+        );
+    }
+
+    // Options layout, two centered rows of buttons
+    let button_row = |area: Rect, count: usize| {
+        let mut constraints = vec![Constraint::Fill(1)];
+        constraints.extend(vec![Constraint::Length(18); count]);
+        constraints.push(Constraint::Fill(1));
+        Layout::default()
+            .direction(Direction::Horizontal)
+            .constraints(constraints)
+            .split(area)
+    };
+    let rows = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints(vec![
+            Constraint::Length(1),
+            Constraint::Length(1),
+            Constraint::Length(1),
+            Constraint::Fill(1),
+        ])
+        .split(vertical_layout[5]);
+    let first_row = button_row(rows[0], 5);
+    let second_row = button_row(rows[2], 4);
+    for (layout, action, label) in [
+        (first_row[1], Action::Restart, "Restart"),
+        (first_row[2], Action::Retry, "Retry"),
+        (first_row[3], Action::Export, "Export"),
+        (first_row[4], Action::Share, "Share"),
+        (first_row[5], Action::Quit, "Quit"),
+        (second_row[1], Action::ResultsView, "View"),
+        (second_row[2], Action::Lessons, "Lessons"),
+        (second_row[3], Action::History, "History"),
+        (second_row[4], Action::Drill, "Drill"),
+    ] {
+        render_options_keybind_block(f, layout, action, label, app);
+    }
+}
+
+/// Round steps between the ticks of the axes
+const WPM_STEPS: [f64; 9] = [5.0, 10.0, 20.0, 25.0, 50.0, 100.0, 200.0, 250.0, 500.0];
+const SECS_STEPS: [f64; 10] = [1.0, 2.0, 5.0, 10.0, 15.0, 30.0, 60.0, 120.0, 300.0, 600.0];
+
+/// The upper bound of an axis and its ticks, a round step apart with at most
+/// five steps up to `max`
+fn axis_ticks(max: f64, steps: &[f64]) -> (f64, Vec<f64>) {
+    let step = steps
+        .iter()
+        .copied()
+        .find(|step| max / step <= 5.0)
+        .unwrap_or((max / 5.0).ceil());
+    let count = (max / step).ceil().max(1.0) as usize;
+    let ticks: Vec<f64> = (0..=count).map(|idx| idx as f64 * step).collect();
+    (count as f64 * step, ticks)
+}
+
+/// Plots the WPM since the start with the raw WPM, or the WPM over the last
+/// few seconds, marking the errors on the line at the time they were made
+fn render_wpm_chart(f: &mut Frame, layout: Rect, app: &App, view: ResultsView) {
+    let series = wpm_series(&app.stats);
+    let (title, wpm_data) = match view {
+        ResultsView::Rolling => ("Rolling WPM chart", &series.rolling),
+        _ => ("WPM chart", &series.net),
+    };
+    let error_data: DataPoints = series
+        .errors
+        .iter()
+        .map(|secs| (*secs, wpm_at(wpm_data, *secs)))
+        .collect();
+
+    let mut datasets = vec![];
+    if view == ResultsView::Chart {
+        datasets.push(
+            Dataset::default()
+                .name("Raw")
+                .marker(symbols::Marker::Braille)
+                .graph_type(GraphType::Line)
+                .style(Style::default().fg(SLATE.c500))
+                .data(&series.raw),
+        );
+    }
+    let name = match view {
+        ResultsView::Rolling => format!("Last {:.0}s", ROLLING_WINDOW_SECS),
+        _ => "Net".to_string(),
+    };
+    datasets.extend([
         Dataset::default()
-            .name("WPM")
+            .name(name)
             .marker(symbols::Marker::Braille)
             .graph_type(GraphType::Line)
             .style(Style::default().yellow())
-            .data(&wpm_data),
-        // Scatter chart
+            .data(wpm_data),
         Dataset::default()
             .name("Errors")
             .marker(symbols::Marker::Dot)
             .graph_type(GraphType::Scatter)
             .style(Style::default().red())
             .data(&error_data),
-    ];
+    ]);
 
     let max_secs = app.stats.last().unwrap().duration_since_start.as_secs_f64();
-    // Create the X axis and define its properties
+    let (secs_bound, secs_ticks) = axis_ticks(max_secs, &SECS_STEPS);
     let x_axis = Axis::default()
         .title("Time".green())
         .style(Style::default().white())
-        .bounds([0.0, max_secs])
-        .labels(vec!["0".into(), format!("{:.2}", max_secs).into()]);
+        .bounds([0.0, secs_bound])
+        .labels(
+            secs_ticks
+                .iter()
+                .map(|secs| Span::from(format!("{:.0}s", secs)))
+                .collect(),
+        );
 
-    let max_wpm = wpm_data
-        .iter()
-        .map(|&(_, wpm)| wpm)
-        .max_by(|a, b| a.partial_cmp(b).unwrap())
-        .unwrap_or(0.0);
-    // Create the Y axis and define its properties
+    // The raw WPM is never below the net WPM
+    let highest = match view {
+        ResultsView::Chart => &series.raw,
+        _ => wpm_data,
+    };
+    let max_wpm = highest.iter().map(|&(_, wpm)| wpm).fold(0.0, f64::max);
+    let (wpm_bound, wpm_ticks) = axis_ticks(max_wpm, &WPM_STEPS);
+    let y_labels: Vec<String> = wpm_ticks.iter().map(|wpm| format!("{:.0}", wpm)).collect();
+    let y_labels_width = y_labels.iter().map(String::len).max().unwrap_or(0) as u16;
     let y_axis = Axis::default()
         .title("WPM".green())
         .style(Style::default().white())
-        .bounds([0.0, max_wpm + 10.0])
-        .labels(vec![
-            "0".into(),
-            format!("{:.0}", max_wpm / 2.0).into(),
-            format!("{:.0}", max_wpm).into(),
-        ]);
+        .bounds([0.0, wpm_bound])
+        .labels(y_labels.into_iter().map(Span::from).collect());
 
     // Create the chart and link all the parts together
-    let title = Title::from("WPM chart".white().bold());
+    let title = Title::from(title.white().bold());
+    let block = Block::new().title(title.alignment(Alignment::Center));
+    let inner = block.inner(layout);
     let chart = Chart::new(datasets)
-        .block(Block::new().title(title.alignment(Alignment::Center)))
+        .block(block)
         .x_axis(x_axis)
-        .y_axis(y_axis);
-    f.render_widget(chart, graph_layout[1]);
+        .y_axis(y_axis)
+        // Keep the legend on charts only a dozen rows high
+        .hidden_legend_constraints((Constraint::Ratio(1, 4), Constraint::Ratio(1, 2)));
+    f.render_widget(chart, layout);
 
-    // Stats layout
-    let stats_layout = Layout::default()
-        .direction(Direction::Horizontal)
-        .constraints(vec![
-            Constraint::Fill(2),
-            Constraint::Length(18),
-            Constraint::Fill(1),
-            Constraint::Length(18),
-            Constraint::Fill(1),
-            Constraint::Length(18),
-            Constraint::Fill(2),
-        ])
-        .split(vertical_layout[2]);
-    render_average_wpm(f, stats_layout[1], app);
-    render_errors(f, stats_layout[3], app);
-    render_accuracy(f, stats_layout[5], app);
+    // Where the chart draws the data, right of the Y axis and above the X axis,
+    // as laid out by the chart itself
+    let left = inner.x + y_labels_width.min(inner.width / 3) + 1;
+    let plot = Rect {
+        x: left,
+        width: inner.right().saturating_sub(left),
+        height: inner.height.saturating_sub(2),
+        ..inner
+    };
+    if let Some(position) = app.mouse_position.filter(|p| plot.contains(*p)) {
+        let secs = (position.x - plot.x) as f64 / plot.width.max(2) as f64 * secs_bound;
+        render_wpm_tooltip(f, position, wpm_data, secs);
+    }
+}
 
-    // Options layout
-    let options_layout = Layout::default()
-        .direction(Direction::Horizontal)
-        .constraints(vec![
-            Constraint::Fill(1),
-            Constraint::Length(18),
-            Constraint::Length(18),
-            Constraint::Fill(1),
-        ])
-        .split(vertical_layout[4]);
-    render_options_keybind_block(f, options_layout[1], "r", "Restart");
-    render_options_keybind_block(f, options_layout[2], "q", "Quit");
+/// Shows the WPM at the point of the chart the mouse is over
+fn render_wpm_tooltip(f: &mut Frame, position: Position, wpm_data: &DataPoints, secs: f64) {
+    let Some(&(time, wpm)) = wpm_data
+        .iter()
+        .min_by(|a, b| (a.0 - secs).abs().total_cmp(&(b.0 - secs).abs()))
+    else {
+        return;
+    };
+
+    let text = format!(" {:.0}s: {:.0} WPM ", time, wpm);
+    let area = f.size();
+    let width = text.len() as u16;
+    // Keep the tooltip on screen, next to the mouse
+    let tooltip = Rect {
+        x: (position.x + 1).min(area.right().saturating_sub(width)),
+        y: position.y.saturating_sub(1),
+        width: width.min(area.width),
+        height: 1,
+    };
+    f.render_widget(Clear, tooltip);
+    f.render_widget(
+        Paragraph::new(text).style(Style::default().bold().fg(SLATE.c950).bg(YELLOW.c400)),
+        tooltip,
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::text_generator::{find_words, Character};
+    use ratatui::backend::TestBackend;
+    use ratatui::Terminal;
+
+    #[test]
+    fn each_button_is_clicked_on_its_own_row() {
+        let mut app = App::new();
+        app.lines = vec!["ab".chars().map(Character::new).collect()];
+        app.words = find_words(&app.lines);
+        app.check_character('a');
+        app.check_character('b');
+        assert!(app.showing_stats);
+        let mut terminal = Terminal::new(TestBackend::new(100, 30)).unwrap();
+        terminal.draw(|f| render_graph(f, &mut app)).unwrap();
+
+        let clicked = |position: Position| {
+            app.click_areas
+                .iter()
+                .find(|(area, _)| area.contains(position))
+                .map(|(_, action)| *action)
+        };
+        let button = |action| {
+            app.click_areas
+                .iter()
+                .find(|(_, a)| *a == action)
+                .map(|(area, _)| *area)
+                .unwrap()
+        };
+
+        let history = button(Action::History);
+        assert_eq!(history.height, 1);
+        let center = Position::new(history.x + history.width / 2, history.y);
+        assert_eq!(clicked(center), Some(Action::History));
+        for (area, action) in &app.click_areas {
+            assert_eq!(clicked(Position::new(area.x, area.y)), Some(*action));
+        }
+    }
 }
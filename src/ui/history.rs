@@ -0,0 +1,90 @@
+use crate::keymap::Action;
+use crate::stats::{finger_stats, format_date};
+use crate::ui::fingers::render_finger_stats;
+use crate::App;
+use ratatui::style::palette::tailwind::{AMBER, SLATE};
+use ratatui::{
+    layout::{Constraint, Direction, Layout},
+    prelude::*,
+    style::Style,
+    text::Line,
+    widgets::{List, ListItem},
+    Frame,
+};
+
+/// Lists the past tests, newest first, with the finger stats of the selected
+/// one below
+pub fn render_history(f: &mut Frame, app: &mut App) {
+    let vertical_layout = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints(vec![
+            Constraint::Length(1),
+            Constraint::Length(1),
+            Constraint::Fill(1),
+            Constraint::Length(1),
+            Constraint::Length(13),
+            Constraint::Length(1),
+        ])
+        .split(f.size());
+
+    let title_text = Span::from("History").bold().white().to_centered_line();
+    f.render_widget(title_text, vertical_layout[0]);
+
+    if app.history.is_empty() {
+        f.render_widget(
+            Line::from("No tests taken yet.").centered(),
+            vertical_layout[2],
+        );
+    } else {
+        let items: Vec<ListItem> = app
+            .history
+            .iter()
+            .rev()
+            .map(|result| {
+                let item = ListItem::new(format!(
+                    "{}  {:<14} {:>5.0} WPM {:>5.0}%{}",
+                    format_date(result.timestamp),
+                    result.mode,
+                    result.wpm,
+                    result.accuracy,
+                    if result.is_valid() { "" } else { "  idle" }
+                ));
+                if result.is_valid() {
+                    item
+                } else {
+                    item.style(Style::default().fg(AMBER.c400))
+                }
+            })
+            .collect();
+        let list = List::new(items)
+            .style(Style::default().fg(SLATE.c400))
+            .highlight_style(Style::default().bold().fg(SLATE.c50).bg(SLATE.c800));
+        let list_area = Layout::default()
+            .direction(Direction::Horizontal)
+            .constraints(vec![
+                Constraint::Fill(1),
+                Constraint::Length(58),
+                Constraint::Fill(1),
+            ])
+            .split(vertical_layout[2]);
+        f.render_stateful_widget(list, list_area[1], &mut app.history_state);
+    }
+
+    if let Some(result) = app.selected_history_result() {
+        let stats = finger_stats(std::slice::from_ref(result));
+        render_finger_stats(f, vertical_layout[4], &stats);
+    }
+
+    f.render_widget(
+        Line::from(format!(
+            "{}/{} to select, {} or {} to close",
+            app.keymap.key(Action::Previous),
+            app.keymap.key(Action::Next),
+            app.keymap.key(Action::History),
+            app.keymap.key(Action::Back)
+        ))
+        .centered()
+        .style(Style::default().fg(SLATE.c500)),
+        vertical_layout[5],
+    );
+}
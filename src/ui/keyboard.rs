@@ -0,0 +1,73 @@
+use crate::layout::Finger;
+use crate::App;
+use ratatui::style::palette::tailwind::{Palette, AMBER, BLUE, EMERALD, RED, SLATE, VIOLET};
+use ratatui::{
+    prelude::*,
+    style::Style,
+    text::{Line, Span},
+    widgets::Paragraph,
+    Frame,
+};
+
+/// How far each row is shifted to the right, like on a real keyboard
+const ROW_OFFSETS: [usize; 3] = [0, 1, 3];
+const SPACE_BAR_WIDTH: usize = 23;
+
+fn finger_palette(finger: Finger) -> Palette {
+    match finger {
+        Finger::LeftPinky | Finger::RightPinky => VIOLET,
+        Finger::LeftRing | Finger::RightRing => BLUE,
+        Finger::LeftMiddle | Finger::RightMiddle => EMERALD,
+        Finger::LeftIndex | Finger::RightIndex => AMBER,
+    }
+}
+
+/// Renders the keys of the chosen layout coloured by finger, highlighting the
+/// next key to press and the key of the last mistake.
+pub fn render_keyboard(f: &mut Frame, layout: Rect, app: &App) {
+    let keyboard_layout = *app.layout.current();
+    let next = app
+        .lines
+        .get(app.cur_line)
+        .and_then(|line| line.get(app.position))
+        .and_then(|c| c.c.to_lowercase().next());
+    let mistake = app
+        .stats
+        .last()
+        .filter(|event| event.error)
+        .and_then(|event| event.typed.to_lowercase().next());
+
+    let key_style = |key: char, palette: Palette| {
+        if Some(key) == mistake {
+            Style::default().bold().fg(SLATE.c50).bg(RED.c600)
+        } else if Some(key) == next {
+            Style::default().bold().fg(SLATE.c950).bg(palette.c400)
+        } else {
+            Style::default().fg(palette.c400).bg(SLATE.c900)
+        }
+    };
+
+    let mut lines: Vec<Line> = keyboard_layout
+        .rows()
+        .iter()
+        .enumerate()
+        .map(|(row, keys)| {
+            let mut spans = vec![Span::from(" ".repeat(ROW_OFFSETS[row]))];
+            for key in keys.chars() {
+                let palette = keyboard_layout.finger(key).map_or(SLATE, finger_palette);
+                spans.push(Span::styled(format!(" {} ", key), key_style(key, palette)));
+                spans.push(Span::from(" "));
+            }
+            // Pad the rows to the same width so centering keeps them staggered
+            spans.push(Span::from(" ".repeat(ROW_OFFSETS[2] - ROW_OFFSETS[row])));
+            Line::from(spans)
+        })
+        .collect();
+    lines.push(Line::from(Span::styled(
+        format!("{:^width$}", "space", width = SPACE_BAR_WIDTH),
+        key_style(' ', SLATE),
+    )));
+
+    let keyboard = Paragraph::new(lines).centered();
+    f.render_widget(keyboard, layout);
+}
@@ -0,0 +1,78 @@
+use crate::keymap::Action;
+use crate::App;
+use ratatui::style::palette::tailwind::{EMERALD, SLATE};
+use ratatui::{
+    layout::{Constraint, Direction, Layout},
+    prelude::*,
+    style::Style,
+    text::Line,
+    widgets::{List, ListItem},
+    Frame,
+};
+
+/// Lists the lessons of the course, marking the passed and the locked ones
+pub fn render_lessons(f: &mut Frame, app: &mut App) {
+    let vertical_layout = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints(vec![
+            Constraint::Length(1),
+            Constraint::Length(1),
+            Constraint::Fill(1),
+            Constraint::Length(1),
+            Constraint::Length(1),
+        ])
+        .split(f.size());
+
+    let title_text = Span::from(app.course.name.clone())
+        .bold()
+        .white()
+        .to_centered_line();
+    f.render_widget(title_text, vertical_layout[0]);
+
+    let items: Vec<ListItem> = app
+        .course
+        .lessons
+        .iter()
+        .enumerate()
+        .map(|(idx, lesson)| {
+            let text = format!(
+                "{:>2}. {:<32} {:>3.0} WPM {:>3.0}%",
+                idx + 1,
+                lesson.name,
+                lesson.target_wpm,
+                lesson.target_accuracy
+            );
+            if app.course_progress.is_passed(&app.course, idx) {
+                ListItem::new(format!("✓ {}", text)).style(Style::default().fg(EMERALD.c400))
+            } else if app.course_progress.is_unlocked(&app.course, idx) {
+                ListItem::new(format!("  {}", text)).style(Style::default().fg(SLATE.c50))
+            } else {
+                ListItem::new(format!("  {}", text)).style(Style::default().fg(SLATE.c600))
+            }
+        })
+        .collect();
+    let list = List::new(items).highlight_style(Style::default().bold().bg(SLATE.c800));
+    let list_area = Layout::default()
+        .direction(Direction::Horizontal)
+        .constraints(vec![
+            Constraint::Fill(1),
+            Constraint::Length(54),
+            Constraint::Fill(1),
+        ])
+        .split(vertical_layout[2]);
+    f.render_stateful_widget(list, list_area[1], &mut app.lessons_state);
+
+    f.render_widget(
+        Line::from(format!(
+            "{}/{} to select, {} to start, {} or {} to close",
+            app.keymap.key(Action::Previous),
+            app.keymap.key(Action::Next),
+            app.keymap.key(Action::Select),
+            app.keymap.key(Action::Lessons),
+            app.keymap.key(Action::Back)
+        ))
+        .centered()
+        .style(Style::default().fg(SLATE.c500)),
+        vertical_layout[4],
+    );
+}
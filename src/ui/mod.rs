@@ -1,16 +1,33 @@
 mod common;
+mod fingers;
 mod graph;
+mod history;
+mod keyboard;
+mod lessons;
+mod resume;
 mod typing;
+mod words;
 
 use crate::ui::graph::render_graph;
+use crate::ui::history::render_history;
+use crate::ui::lessons::render_lessons;
+use crate::ui::resume::render_resume;
 use crate::ui::typing::render_typing;
-use crate::App;
+use crate::{App, TYPING_AREA_WIDTH};
 use ratatui::widgets::Wrap;
 use ratatui::Frame;
 
 pub fn ui(f: &mut Frame, app: &mut App) {
+    // Only what is drawn in this frame can be clicked
+    app.click_areas.clear();
     if app.showing_size_warning {
-        render_size_warning(f);
+        render_size_warning(f, app);
+    } else if app.resume_position.is_some() {
+        render_resume(f, app);
+    } else if app.showing_lessons {
+        render_lessons(f, app);
+    } else if app.showing_history {
+        render_history(f, app);
     } else if app.showing_stats {
         render_graph(f, app);
     } else {
@@ -18,8 +35,12 @@ pub fn ui(f: &mut Frame, app: &mut App) {
     }
 }
 
-fn render_size_warning(f: &mut Frame) {
-    let text = "Please resize the terminal to at least 72x20.";
+fn render_size_warning(f: &mut Frame, app: &App) {
+    let text = format!(
+        "Please resize the terminal to at least {}x{}.",
+        TYPING_AREA_WIDTH,
+        app.min_height()
+    );
     f.render_widget(
         ratatui::widgets::Paragraph::new(text).wrap(Wrap { trim: true }),
         f.size(),
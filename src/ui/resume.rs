@@ -0,0 +1,45 @@
+use crate::keymap::Action;
+use crate::App;
+use ratatui::style::palette::tailwind::SLATE;
+use ratatui::{
+    layout::{Constraint, Direction, Layout},
+    prelude::*,
+    style::Style,
+    text::Line,
+    widgets::Paragraph,
+    Frame,
+};
+
+/// Offers to resume the custom text where the last session stopped
+pub fn render_resume(f: &mut Frame, app: &App) {
+    let (Some(text), Some(position)) = (&app.custom_text, app.resume_position) else {
+        return;
+    };
+
+    let vertical_layout = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints(vec![
+            Constraint::Fill(1),
+            Constraint::Length(5),
+            Constraint::Fill(1),
+        ])
+        .split(f.size());
+
+    let prompt = Paragraph::new(vec![
+        Line::from(format!("Resume {}?", text.name)).style(Style::default().bold().white()),
+        Line::from(""),
+        Line::from(format!(
+            "You stopped at {:.0}% of the text last time.",
+            text.percent(position)
+        )),
+        Line::from(""),
+        Line::from(format!(
+            "{} to resume, {} to start from the beginning",
+            app.keymap.key(Action::ResumeText),
+            app.keymap.key(Action::StartOver)
+        ))
+        .style(Style::default().fg(SLATE.c500)),
+    ])
+    .centered();
+    f.render_widget(prompt, vertical_layout[1]);
+}
@@ -1,33 +1,42 @@
-use crate::options::Highlight;
-use crate::text_generator::CharState;
+use crate::keymap::Action;
+use crate::options::{Caret, DailyGoal, Highlight, Labeled, LiveStats, Scrolling, TextMode};
+use crate::stats::GoalStatus;
+use crate::text_generator::{CharState, Character};
 use crate::ui::common::{
     get_colors, render_accuracy, render_average_wpm, render_cyclic_options_block,
     render_options_keybind_block,
 };
-use crate::utils::get_nth_word_boundaries;
-use crate::{App, TYPING_AREA_WIDTH};
-use ratatui::style::palette::tailwind::SLATE;
+use crate::ui::keyboard::render_keyboard;
+use crate::utils::{get_nth_word_boundaries, recent_stats, LIVE_WINDOW_SECS};
+use crate::{App, MENU_HEIGHT, TYPING_AREA_WIDTH};
+use ratatui::style::palette::tailwind::{AMBER, SLATE};
 use ratatui::{
     layout::{Constraint, Direction, Layout},
     prelude::*,
     style::Style,
+    symbols::border,
     text::{Line, Span},
-    widgets::{Block, Paragraph},
+    widgets::{Block, Borders, Paragraph, Sparkline},
     Frame,
 };
 
+/// The space left on either side of the text typed in zen mode
+const FREE_TEXT_MARGIN: u16 = 3;
+
 pub fn render_typing(f: &mut Frame, app: &mut App) {
+    let keyboard_height = app.keyboard_height();
     let vertical_layout = Layout::default()
         .direction(Direction::Vertical)
         .constraints(vec![
             Constraint::Length(1),
             Constraint::Fill(1),
-            Constraint::Length(5),
+            Constraint::Length(app.text_height()),
+            Constraint::Length(keyboard_height),
             Constraint::Fill(1),
             Constraint::Length(3),
             Constraint::Fill(1),
             Constraint::Length(3),
-            Constraint::Length(5),
+            Constraint::Length(MENU_HEIGHT),
         ])
         .split(f.size());
 
@@ -36,31 +45,107 @@ pub fn render_typing(f: &mut Frame, app: &mut App) {
     f.render_widget(title_text, vertical_layout[0]);
 
     render_typing_area(f, vertical_layout[2], app);
-    render_stats_area(f, vertical_layout[4], app);
-    render_message_area(f, vertical_layout[6], app);
+    if keyboard_height > 0 {
+        let keyboard_area = Rect {
+            y: vertical_layout[3].y + 1,
+            height: keyboard_height - 1,
+            ..vertical_layout[3]
+        };
+        render_keyboard(f, keyboard_area, app);
+    }
+    render_stats_area(f, vertical_layout[5], app);
+    render_message_area(f, vertical_layout[7], app);
     /////////////////////////////////
     // Menu block
     /////////////////////////////////
     if app.pause {
-        let menu_block = Layout::default()
+        let menu_rows = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints(vec![
+                Constraint::Length(4),
+                Constraint::Length(4),
+                Constraint::Length(4),
+                Constraint::Length(1),
+            ])
+            .split(vertical_layout[8]);
+
+        let text_block = Layout::default()
             .direction(Direction::Horizontal)
             .constraints(vec![
                 Constraint::Fill(1),
                 Constraint::Length(13),
-                Constraint::Length(16),
-                Constraint::Length(17),
+                Constraint::Length(14),
                 Constraint::Length(13),
-                Constraint::Length(12),
+                Constraint::Length(17),
+                Constraint::Length(14),
+                Constraint::Fill(1),
+            ])
+            .split(menu_rows[0]);
+
+        render_cyclic_options_block(f, text_block[1], app.number_of_words.clone(), app);
+        render_cyclic_options_block(f, text_block[2], app.capitals.clone(), app);
+        render_cyclic_options_block(f, text_block[3], app.numbers.clone(), app);
+        render_cyclic_options_block(f, text_block[4], app.punctuation.clone(), app);
+        render_cyclic_options_block(f, text_block[5], app.brackets.clone(), app);
+
+        let display_block = Layout::default()
+            .direction(Direction::Horizontal)
+            .constraints(vec![
+                Constraint::Fill(1),
+                Constraint::Length(15),
+                Constraint::Length(10),
+                Constraint::Length(15),
+                Constraint::Length(18),
+                Constraint::Length(14),
+                Constraint::Fill(1),
+            ])
+            .split(menu_rows[1]);
+
+        render_cyclic_options_block(f, display_block[1], app.highlight.clone(), app);
+        render_cyclic_options_block(f, display_block[2], app.visible_lines.clone(), app);
+        render_cyclic_options_block(f, display_block[3], app.scrolling.clone(), app);
+        render_cyclic_options_block(f, display_block[4], app.caret.clone(), app);
+        render_cyclic_options_block(f, display_block[5], app.keyboard.clone(), app);
+
+        let practice_block = Layout::default()
+            .direction(Direction::Horizontal)
+            .constraints(vec![
+                Constraint::Fill(1),
+                Constraint::Length(14),
+                Constraint::Length(14),
+                Constraint::Length(14),
+                Constraint::Length(14),
                 Constraint::Fill(1),
             ])
-            .split(vertical_layout[7]);
+            .split(menu_rows[2]);
 
-        render_cyclic_options_block(f, menu_block[1], app.number_of_words.clone());
-        render_cyclic_options_block(f, menu_block[2], app.difficulty.clone());
-        render_cyclic_options_block(f, menu_block[3], app.highlight.clone());
+        render_cyclic_options_block(f, practice_block[1], app.text_mode.clone(), app);
+        render_cyclic_options_block(f, practice_block[2], app.daily_goal.clone(), app);
+        render_cyclic_options_block(f, practice_block[3], app.layout.clone(), app);
+        render_cyclic_options_block(f, practice_block[4], app.live_stats.clone(), app);
+
+        let actions_block = Layout::default()
+            .direction(Direction::Horizontal)
+            .constraints(vec![
+                Constraint::Fill(1),
+                Constraint::Length(14),
+                Constraint::Length(14),
+                Constraint::Length(14),
+                Constraint::Length(14),
+                Constraint::Length(14),
+                Constraint::Fill(1),
+            ])
+            .split(menu_rows[3]);
 
-        render_options_keybind_block(f, menu_block[4], "r", "Restart");
-        render_options_keybind_block(f, menu_block[5], "q", "Quit");
+        for (layout, action, label) in [
+            (actions_block[1], Action::Restart, "Restart"),
+            (actions_block[2], Action::Retry, "Retry"),
+            (actions_block[3], Action::Lessons, "Lessons"),
+            (actions_block[4], Action::History, "History"),
+            (actions_block[5], Action::Quit, "Quit"),
+        ] {
+            render_options_keybind_block(f, layout, action, label, app);
+        }
     }
 }
 
@@ -74,57 +159,38 @@ fn render_typing_area(f: &mut Frame, layout: Rect, app: &mut App) {
         ])
         .split(layout);
 
+    if *app.text_mode.current() == TextMode::Zen {
+        render_free_text(f, typing_area[1], app);
+        return;
+    }
+    if *app.scrolling.current() == Scrolling::Tape {
+        render_tape(f, typing_area[1], app);
+        return;
+    }
+
+    let visible_lines = *app.visible_lines.current() as usize;
+    // The first visible line, which may be before the start of the text
+    let first_line = match app.scrolling.current() {
+        Scrolling::Top => app.cur_line as isize,
+        _ => app.cur_line as isize - (visible_lines / 2) as isize,
+    };
+
     let mut typing_lines = vec![];
 
-    let (word_start, word_end, line_offset) =
-        get_nth_word_boundaries(app, app.highlight.current().get_words_ahead());
+    let word_boundaries = get_nth_word_boundaries(app, app.highlight.current().get_words_ahead());
 
-    for line_idx in app.cur_line as isize - 2..app.cur_line as isize + 3 {
+    for line_idx in first_line..first_line + visible_lines as isize {
         if line_idx < 0 || line_idx >= app.lines.len() as isize {
             typing_lines.push(Line::from(vec![" ".into()]));
             continue;
         }
 
         let line = app.lines.get(line_idx as usize).unwrap();
-        let mut terminal_line = vec![];
-        for (idx, c) in line.iter().enumerate() {
-            let mut string = c.typed_c.to_string();
-            if c.typed_c == ' ' {
-                string = "\u{00B7}".to_string();
-            } else if c.typed_c == '\n' {
-                string = "¶".to_string();
-            }
-            let mut text = Span::from(string.clone()).style(match c.state {
-                CharState::Untouched => {
-                    Style::default().fg(get_colors(app.cur_line, line_idx as usize, c.c).untyped)
-                }
-                CharState::Correct => {
-                    Style::default().fg(get_colors(app.cur_line, line_idx as usize, c.c).correct)
-                }
-                CharState::Incorrect => {
-                    Style::default().fg(get_colors(app.cur_line, line_idx as usize, c.c).incorrect)
-                }
-            });
-            match app.highlight.current() {
-                Highlight::Character => {
-                    if line_idx == app.cur_line as isize && app.position == idx {
-                        text = text.yellow().underlined().bold();
-                    }
-                }
-                Highlight::Word | Highlight::NextWord | Highlight::TwoWords => {
-                    if line_offset as isize == line_idx - app.cur_line as isize
-                        && idx >= word_start
-                        && idx < word_end
-                        && string != "\u{00B7}".to_string()
-                    {
-                        text = text.yellow().underlined().bold();
-                    }
-                }
-                _ => {}
-            }
-
-            terminal_line.push(text);
-        }
+        let terminal_line: Vec<Span> = line
+            .iter()
+            .enumerate()
+            .map(|(idx, c)| character_span(app, line_idx as usize, idx, c, word_boundaries))
+            .collect();
         typing_lines.push(Line::from(terminal_line));
     }
 
@@ -132,45 +198,237 @@ fn render_typing_area(f: &mut Frame, layout: Rect, app: &mut App) {
 
     f.render_widget(typing_text, typing_area[1]);
 
-    f.set_cursor(
-        typing_area[1].x
-            + ((TYPING_AREA_WIDTH as f32 - app.lines[app.cur_line].len() as f32) / 2.0).ceil()
-                as u16
-            + app.position as u16,
-        typing_area[1].y + 2,
-    );
+    if *app.caret.current() != Caret::Drawn {
+        f.set_cursor(
+            typing_area[1].x
+                + ((TYPING_AREA_WIDTH as f32 - app.lines[app.cur_line].len() as f32) / 2.0).ceil()
+                    as u16
+                + app.position as u16,
+            typing_area[1].y + (app.cur_line as isize - first_line) as u16,
+        );
+    }
+}
+
+/// Renders the whole text as a single line that scrolls horizontally, keeping
+/// the cursor in the middle of the typing area.
+fn render_tape(f: &mut Frame, layout: Rect, app: &mut App) {
+    let word_boundaries = get_nth_word_boundaries(app, app.highlight.current().get_words_ahead());
+
+    let cursor = app.lines[..app.cur_line]
+        .iter()
+        .map(|line| line.len())
+        .sum::<usize>()
+        + app.position;
+    let half_width = (TYPING_AREA_WIDTH / 2) as usize;
+
+    let mut spans = vec![];
+    if cursor < half_width {
+        spans.push(Span::from(" ".repeat(half_width - cursor)));
+    }
+
+    let characters = app
+        .lines
+        .iter()
+        .enumerate()
+        .flat_map(|(line_idx, line)| {
+            line.iter()
+                .enumerate()
+                .map(move |(idx, c)| (line_idx, idx, c))
+        })
+        .skip(cursor.saturating_sub(half_width))
+        .take(TYPING_AREA_WIDTH as usize - half_width.saturating_sub(cursor));
+    for (line_idx, idx, c) in characters {
+        spans.push(character_span(app, line_idx, idx, c, word_boundaries));
+    }
+
+    f.render_widget(Paragraph::new(Line::from(spans)), layout);
+    if *app.caret.current() != Caret::Drawn {
+        f.set_cursor(layout.x + half_width as u16, layout.y);
+    }
+}
+
+/// Renders the text typed in zen mode, wrapped at spaces to the typing area,
+/// keeping the line being typed in view.
+fn render_free_text(f: &mut Frame, layout: Rect, app: &App) {
+    let width = TYPING_AREA_WIDTH as usize - FREE_TEXT_MARGIN as usize * 2;
+    let mut lines: Vec<String> = vec![];
+    for paragraph in app.free_text.split('\n') {
+        let mut line = String::new();
+        for c in paragraph.chars() {
+            if line.chars().count() == width {
+                let rest = match line.rfind(' ') {
+                    Some(space) => line.split_off(space + 1),
+                    None => String::new(),
+                };
+                lines.push(line);
+                line = rest;
+            }
+            line.push(c);
+        }
+        lines.push(line);
+    }
+
+    let visible_lines = match app.scrolling.current() {
+        Scrolling::Tape => 1,
+        _ => *app.visible_lines.current() as usize,
+    };
+    let shown = &lines[lines.len().saturating_sub(visible_lines)..];
+    let mut typing_lines: Vec<Line> = shown
+        .iter()
+        .enumerate()
+        .map(|(idx, line)| {
+            let colors = get_colors(shown.len() - 1 - idx, 'x');
+            Line::from(Span::from(line.clone()).style(Style::default().fg(colors.untyped)))
+        })
+        .collect();
+
+    let cursor_x = shown.last().map_or(0, |line| line.chars().count()) as u16;
+    if *app.caret.current() == Caret::Drawn {
+        if let Some(line) = typing_lines.last_mut() {
+            line.spans.push(Span::from(" ").reversed());
+        }
+    } else {
+        f.set_cursor(
+            layout.x + FREE_TEXT_MARGIN + cursor_x,
+            layout.y + shown.len() as u16 - 1,
+        );
+    }
+
+    let area = layout.inner(&Margin::new(FREE_TEXT_MARGIN, 0));
+    f.render_widget(Paragraph::new(typing_lines), area);
+}
+
+fn character_span(
+    app: &App,
+    line_idx: usize,
+    idx: usize,
+    c: &Character,
+    (word_start, word_end, line_offset): (usize, usize, usize),
+) -> Span<'static> {
+    let mut string = c.typed_c.to_string();
+    if c.typed_c == ' ' {
+        string = "\u{00B7}".to_string();
+    } else if c.typed_c == '\n' {
+        string = "¶".to_string();
+    }
+    let colors = get_colors(line_idx.abs_diff(app.cur_line), c.c);
+    let mut text = Span::from(string.clone()).style(match c.state {
+        CharState::Untouched => Style::default().fg(colors.untyped),
+        CharState::Correct => Style::default().fg(colors.correct),
+        CharState::Incorrect => Style::default().fg(colors.incorrect),
+    });
+    match app.highlight.current() {
+        Highlight::Character if line_idx == app.cur_line && app.position == idx => {
+            text = text.yellow().underlined().bold();
+        }
+        Highlight::Word | Highlight::NextWord | Highlight::TwoWords
+            if line_offset as isize == line_idx as isize - app.cur_line as isize
+                && idx >= word_start
+                && idx < word_end
+                && string != "\u{00B7}" =>
+        {
+            text = text.yellow().underlined().bold();
+        }
+        _ => {}
+    }
+    // The drawn caret replaces the terminal cursor, which stays hidden
+    if *app.caret.current() == Caret::Drawn && line_idx == app.cur_line && app.position == idx {
+        text = text.reversed();
+    }
+
+    text
 }
 
 fn render_stats_area(f: &mut Frame, layout: Rect, app: &App) {
+    let live = *app.live_stats.current() == LiveStats::Shown;
+    let mut constraints = vec![
+        Constraint::Fill(1),
+        Constraint::Length(16),
+        Constraint::Length(16),
+    ];
+    if live {
+        constraints.push(Constraint::Length(28));
+    }
+    constraints.push(Constraint::Fill(1));
     let stats_layout = Layout::default()
         .direction(Direction::Horizontal)
-        .constraints(vec![
-            Constraint::Fill(1),
-            Constraint::Length(16),
-            Constraint::Length(16),
-            Constraint::Fill(1),
-        ])
+        .constraints(constraints)
         .split(layout);
 
     render_average_wpm(f, stats_layout[1], app);
     render_accuracy(f, stats_layout[2], app);
+    if live {
+        render_live_stats(f, stats_layout[3], app);
+    }
+}
+
+/// A sparkline of the WPM of each of the last seconds, next to the accuracy
+/// over them
+fn render_live_stats(f: &mut Frame, layout: Rect, app: &App) {
+    let title = Span::from(format!(" Last {}s ", LIVE_WINDOW_SECS))
+        .style(Style::default().fg(SLATE.c500))
+        .to_centered_line();
+    let block = Block::default()
+        .title(title)
+        .borders(Borders::ALL)
+        .border_style(SLATE.c500)
+        .border_set(border::THICK);
+    let inner = block.inner(layout);
+    f.render_widget(block, layout);
+
+    if app.stats.is_empty() {
+        f.render_widget(Paragraph::new("-").white().centered(), inner);
+        return;
+    }
+    let recent = recent_stats(&app.stats, app.timer.elapsed());
+    let live_layout = Layout::default()
+        .direction(Direction::Horizontal)
+        .constraints(vec![Constraint::Fill(1), Constraint::Length(5)])
+        .split(inner);
+    f.render_widget(
+        Sparkline::default()
+            .data(&recent.wpm)
+            .style(Style::default().yellow()),
+        live_layout[0],
+    );
+    f.render_widget(
+        Paragraph::new(format!("{:.0}%", recent.accuracy))
+            .white()
+            .right_aligned(),
+        live_layout[1],
+    );
 }
 
 fn render_message_area(f: &mut Frame, layout: Rect, app: &App) {
     let block = Block::default();
+    let goal = goal_line(app);
     let message = Paragraph::new(if app.pause {
         vec![
             Line::from(vec![Span::from("PAUSED").white().bold()]),
-            Line::from(vec![Span::from("Press Esc to resume")]),
-            Line::from(vec![]),
+            match &app.notice {
+                Some(notice) => Line::from(vec![Span::from(format!(
+                    "{}, press {} to resume",
+                    notice,
+                    app.keymap.key(Action::Resume)
+                ))
+                .style(Style::default().fg(AMBER.c400))]),
+                None => Line::from(vec![Span::from(format!(
+                    "Press {} to resume",
+                    app.keymap.key(Action::Resume)
+                ))]),
+            },
+            goal,
         ]
     } else {
         vec![
-            Line::from(vec![]),
-            Line::from(vec![]),
-            Line::from(vec![
-                Span::from("Press Esc to pause").style(Style::default().fg(SLATE.c500))
-            ]),
+            practice_line(app),
+            goal,
+            Line::from(vec![Span::from(format!(
+                "Press {} to pause, {} to restart",
+                app.keymap.key(Action::Pause),
+                app.keymap.key(Action::QuickRestart)
+            ))
+            .style(Style::default().fg(SLATE.c500))]),
         ]
     })
     .centered()
@@ -178,3 +436,94 @@ fn render_message_area(f: &mut Frame, layout: Rect, app: &App) {
 
     f.render_widget(message, layout);
 }
+
+/// What is being practised in the modes that track progress: the current
+/// lesson, the keys unlocked so far when learning a layout, or the progress
+/// through a custom text
+fn practice_line(app: &App) -> Line<'static> {
+    match app.text_mode.current() {
+        TextMode::LearnLayout => learning_line(app),
+        TextMode::Lesson => {
+            let lesson = &app.course.lessons[app.lesson];
+            Line::from(vec![
+                Span::from(format!("Lesson {}: ", app.lesson + 1))
+                    .style(Style::default().fg(SLATE.c400)),
+                Span::from(lesson.name.clone()).yellow().bold(),
+                Span::from(format!(
+                    " \u{00B7} {:.0} WPM, {:.0}% accuracy to pass",
+                    lesson.target_wpm, lesson.target_accuracy
+                ))
+                .style(Style::default().fg(SLATE.c400)),
+            ])
+        }
+        TextMode::Custom => match &app.custom_text {
+            Some(text) => Line::from(vec![
+                Span::from(text.name.clone()).yellow().bold(),
+                Span::from(format!(" \u{00B7} {:.0}% done", text.progress()))
+                    .style(Style::default().fg(SLATE.c400)),
+            ]),
+            None => Line::from(vec![]),
+        },
+        TextMode::Drill => Line::from(
+            Span::from(format!(
+                "Drilling {} slow and mistyped words",
+                app.drill_words.len()
+            ))
+            .style(Style::default().fg(SLATE.c400)),
+        ),
+        TextMode::Zen => Line::from(
+            Span::from(format!(
+                "Type anything \u{00B7} press {} to finish",
+                app.keymap.key(Action::Finish)
+            ))
+            .style(Style::default().fg(SLATE.c400)),
+        ),
+        _ => Line::from(vec![]),
+    }
+}
+
+/// The keys unlocked so far when learning a layout, newest last
+fn learning_line(app: &App) -> Line<'static> {
+    let layout = *app.layout.current();
+    let keys = app.layout_progress.unlocked_keys(layout);
+    let (newest, previous) = keys.split_last().unwrap();
+    Line::from(vec![
+        Span::from(format!(
+            "Learning {}: {}/{} keys ",
+            layout.label(),
+            keys.len(),
+            layout.learning_order().len()
+        ))
+        .style(Style::default().fg(SLATE.c400)),
+        Span::from(previous.iter().collect::<String>()).style(Style::default().fg(SLATE.c500)),
+        Span::from(newest.to_string()).yellow().bold(),
+    ])
+}
+
+/// Today's progress towards the daily goal and the current practice streak
+fn goal_line(app: &App) -> Line<'static> {
+    let Some(GoalStatus {
+        done,
+        target,
+        streak,
+    }) = app.goal_status
+    else {
+        return Line::from(vec![]);
+    };
+
+    let unit = match app.daily_goal.current() {
+        DailyGoal::Minutes(_) => "minutes",
+        _ => "tests",
+    };
+    let progress = if done >= target {
+        Span::from(format!("Daily goal reached: {}/{} {}", done, target, unit)).green()
+    } else {
+        Span::from(format!("Today: {}/{} {}", done, target, unit)).yellow()
+    };
+
+    Line::from(vec![
+        progress,
+        Span::from(format!(" \u{00B7} {} day streak", streak))
+            .style(Style::default().fg(SLATE.c400)),
+    ])
+}
@@ -0,0 +1,93 @@
+use crate::keymap::Action;
+use crate::App;
+use ratatui::style::palette::tailwind::{AMBER, RED, SLATE};
+use ratatui::{
+    layout::{Constraint, Direction, Layout},
+    prelude::*,
+    style::Style,
+    text::Line,
+    widgets::{List, ListItem},
+    Frame,
+};
+
+/// Lists the words of the test with their speed and errors, marking the
+/// slowest ones and the mistyped ones
+pub fn render_word_results(f: &mut Frame, layout: Rect, app: &mut App) {
+    let results = app.word_results();
+    let vertical_layout = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints(vec![
+            Constraint::Length(1),
+            Constraint::Fill(1),
+            Constraint::Length(1),
+        ])
+        .split(layout);
+    let columns = Layout::default()
+        .direction(Direction::Horizontal)
+        .constraints(vec![
+            Constraint::Fill(1),
+            Constraint::Length(50),
+            Constraint::Fill(1),
+        ]);
+
+    if results.is_empty() {
+        f.render_widget(
+            Line::from("No words were typed.").centered(),
+            vertical_layout[1],
+        );
+        return;
+    }
+
+    f.render_widget(
+        Line::from(format!(
+            "  {:<24}{:>6}{:>8}  {}",
+            "Word", "WPM", "Errors", "Correct"
+        ))
+        .style(Style::default().bold().white()),
+        columns.split(vertical_layout[0])[1],
+    );
+
+    let items: Vec<ListItem> = results
+        .iter()
+        .map(|result| {
+            let wpm = result
+                .wpm
+                .map_or("-".to_string(), |wpm| format!("{:.0}", wpm));
+            let text = format!(
+                "  {:<24}{:>6}{:>8}  {}",
+                result.text,
+                wpm,
+                result.errors,
+                if result.correct { "✓" } else { "✗" }
+            );
+            let color = if result.errors > 0 || !result.correct {
+                RED.c400
+            } else if result.slow {
+                AMBER.c400
+            } else {
+                SLATE.c300
+            };
+            ListItem::new(text).style(Style::default().fg(color))
+        })
+        .collect();
+    let list = List::new(items).highlight_style(Style::default().bold().bg(SLATE.c800));
+    f.render_stateful_widget(
+        list,
+        columns.split(vertical_layout[1])[1],
+        &mut app.words_state,
+    );
+
+    let drilled = results.iter().filter(|r| r.needs_drill()).count();
+    f.render_widget(
+        Line::from(format!(
+            "{}/{} to scroll, {} to drill the {} slow and mistyped words",
+            app.keymap.key(Action::Previous),
+            app.keymap.key(Action::Next),
+            app.keymap.key(Action::Drill),
+            drilled
+        ))
+        .centered()
+        .style(Style::default().fg(SLATE.c500)),
+        vertical_layout[2],
+    );
+}
@@ -1,83 +1,88 @@
+use crate::options::Frequency;
 use crate::App;
 use crate::TypingEvent;
 use rand::seq::SliceRandom;
 use rand::{thread_rng, Rng};
+use std::time::Duration;
 
-pub fn get_nth_word_boundaries(app: &mut App, word_offset: usize) -> (usize, usize, usize) {
-    let mut word_start = 0;
-    let mut word_end = 0;
-    let mut words_found = 0;
-    let mut found_current = false;
-    let mut line_offset = 0;
-
-    // Iterate over lines starting from the current line
-    for (line_idx, line) in app.lines.iter().enumerate().skip(app.cur_line) {
-        if line_idx > app.cur_line {
-            line_offset = line_idx - app.cur_line; // Update line offset
-        }
+/// The start and end of the word `word_offset` words after the one being
+/// typed, and how many lines below the current line it is. The word being
+/// typed starts at the cursor.
+pub fn get_nth_word_boundaries(app: &App, word_offset: usize) -> (usize, usize, usize) {
+    let current = app.words.iter().position(|word| {
+        word.line > app.cur_line || (word.line == app.cur_line && word.end > app.position)
+    });
+    let Some(current) = current else {
+        return (0, 0, 0);
+    };
 
-        for idx in 0..line.len() {
-            if !found_current {
-                if idx == app.position && line_idx == app.cur_line {
-                    // found our first word
-                    word_start = idx;
-                    found_current = true;
-                }
-            } else {
-                if idx == 0 {
-                    word_start = 0;
-                    words_found += 1;
-                    continue;
-                }
+    let word = app.words[(current + word_offset).min(app.words.len() - 1)];
+    let start = if word.line == app.cur_line {
+        word.start.max(app.position)
+    } else {
+        word.start
+    };
+    (start, word.end, word.line - app.cur_line)
+}
 
-                if (idx > 0 && line[idx - 1].c.is_whitespace()) || idx == line.len() - 1 {
-                    words_found += 1;
-                    word_end = idx;
-                    if line_offset > 0 && word_start > word_end {
-                        word_start = 0;
-                    }
+/// Picks `count` of the `total` indices at random, or all of them if there are
+/// fewer, returning whether each index was picked
+fn pick_indices(total: usize, count: usize) -> Vec<bool> {
+    let mut picked = vec![false; total];
+    let mut indices: Vec<usize> = (0..total).collect();
+    indices.shuffle(&mut thread_rng());
+    for &i in &indices[..count.min(total)] {
+        picked[i] = true;
+    }
+    picked
+}
+
+/// Capitalize `frequency` of the words, with 20% of those being fully
+/// capitalized. Numbers have nothing to capitalize, so the capitals all go to
+/// the words with letters.
+pub fn capitalize(vec: Vec<String>, frequency: Frequency) -> Vec<String> {
+    let has_letters: Vec<bool> = vec
+        .iter()
+        .map(|s| s.chars().any(char::is_alphabetic))
+        .collect();
+    let letter_count = has_letters.iter().filter(|&&letters| letters).count();
+    let capitalize_count = frequency.count_of(vec.len()).min(letter_count);
+    let full_capitalize_count = Frequency(20).count_of(capitalize_count);
+    // Which of the words with letters are capitalized, and which of those
+    // are fully capitalized
+    let mut capitalize = pick_indices(letter_count, capitalize_count).into_iter();
+    let mut full_capitalize = pick_indices(capitalize_count, full_capitalize_count).into_iter();
 
-                    if words_found > word_offset {
-                        return (word_start, word_end, line_offset);
+    vec.into_iter()
+        .zip(has_letters)
+        .map(|(s, letters)| {
+            if !letters || capitalize.next() != Some(true) {
+                s
+            } else if full_capitalize.next() == Some(true) {
+                s.to_uppercase()
+            } else {
+                let mut chars = s.chars();
+                match chars.next() {
+                    None => s,
+                    Some(first_char) => {
+                        first_char.to_uppercase().collect::<String>() + chars.as_str()
                     }
-                    word_start = idx; // Start of a new word
                 }
             }
-        }
-    }
-
-    // Return the last word found if we run out of text, along with line offset
-    (word_start, word_end, line_offset)
+        })
+        .collect()
 }
 
-/// Capitalize 20% of the words, with 20% of those being fully capitalized
-pub fn capitalize_20_percent(vec: Vec<String>) -> Vec<String> {
+/// Convert `frequency` of the words to numbers
+pub fn convert_to_numbers(vec: Vec<String>, frequency: Frequency) -> Vec<String> {
     let mut rng = thread_rng();
-    let total_count = vec.len();
-    let capitalize_count = (total_count as f64 * 0.20).ceil() as usize;
-    let full_capitalize_count = (capitalize_count as f64 * 0.20).ceil() as usize;
-
-    let mut indices: Vec<usize> = (0..total_count).collect();
-    indices.shuffle(&mut rng);
-
-    let capitalize_indices = &indices[..capitalize_count];
-    let full_capitalize_indices = &capitalize_indices[..full_capitalize_count];
+    let convert_indices = pick_indices(vec.len(), frequency.count_of(vec.len()));
 
     vec.into_iter()
-        .enumerate()
-        .map(|(i, s)| {
-            if capitalize_indices.contains(&i) {
-                if full_capitalize_indices.contains(&i) {
-                    s.to_uppercase()
-                } else {
-                    let mut chars = s.chars();
-                    match chars.next() {
-                        None => s,
-                        Some(first_char) => {
-                            first_char.to_uppercase().collect::<String>() + chars.as_str()
-                        }
-                    }
-                }
+        .zip(convert_indices)
+        .map(|(s, convert)| {
+            if convert {
+                rng.gen_range(0..10000).to_string()
             } else {
                 s
             }
@@ -85,100 +90,353 @@ pub fn capitalize_20_percent(vec: Vec<String>) -> Vec<String> {
         .collect()
 }
 
-/// Convert 15% of the words to numbers
-pub fn convert_15_percent_to_numbers(vec: Vec<String>) -> Vec<String> {
+/// Add a punctuation mark after `frequency` of the words
+pub fn add_punctuation(vec: Vec<String>, frequency: Frequency) -> Vec<String> {
     let mut rng = thread_rng();
-    let total_count = vec.len();
-    let convert_count = (total_count as f64 * 0.1).ceil() as usize;
+    let modify_indices = pick_indices(vec.len(), frequency.count_of(vec.len()));
 
-    let mut indices: Vec<usize> = (0..total_count).collect();
-    indices.shuffle(&mut rng);
-
-    let convert_indices = &indices[..convert_count];
+    let common_symbols = [',', '.'];
+    let less_common_symbols = ['?', '!', ':', ';', '-'];
 
     vec.into_iter()
-        .enumerate()
-        .map(|(i, s)| {
-            if convert_indices.contains(&i) {
-                rng.gen_range(0..10000).to_string()
-            } else {
-                s
+        .zip(modify_indices)
+        .map(|(s, modify)| {
+            if !modify {
+                return s;
             }
+            // More often add common symbols
+            let symbol = if rng.gen_bool(0.6) {
+                common_symbols.choose(&mut rng).unwrap()
+            } else {
+                less_common_symbols.choose(&mut rng).unwrap()
+            };
+            format!("{}{}", s, symbol)
         })
         .collect()
 }
 
-/// Add symbols to 20% of the words
-pub fn add_symbols(vec: Vec<String>) -> Vec<String> {
+/// Surround `frequency` of the words with brackets or quotes
+pub fn add_brackets(vec: Vec<String>, frequency: Frequency) -> Vec<String> {
     let mut rng = thread_rng();
-    let total_count = vec.len();
-    let modify_count = (total_count as f64 * 0.2).ceil() as usize;
+    let modify_indices = pick_indices(vec.len(), frequency.count_of(vec.len()));
 
-    let mut indices: Vec<usize> = (0..total_count).collect();
-    indices.shuffle(&mut rng);
+    let surrounding_symbols = ["()", "[]", "{}", "<>", "\"\"", "''"];
 
-    let modify_indices = &indices[..modify_count];
+    vec.into_iter()
+        .zip(modify_indices)
+        .map(|(s, modify)| {
+            if !modify {
+                return s;
+            }
+            let surrounding = surrounding_symbols.choose(&mut rng).unwrap();
+            let (left, right) = surrounding.split_at(1);
+            format!("{}{}{}", left, s, right)
+        })
+        .collect()
+}
 
-    let common_symbols = [',', '.'];
-    let less_common_symbols = ['?', '_', '-', '!', ':'];
-    let surrounding_symbols = ["()", "\"\""];
+/// Prepares a text given by the user for typing: tabs become spaces and
+/// trailing whitespace is removed
+pub fn prepare_text(text: &str) -> String {
+    text.lines()
+        .map(|line| line.replace('\t', "    ").trim_end().to_string())
+        .collect::<Vec<String>>()
+        .join("\n")
+        .trim()
+        .to_string()
+}
 
-    vec.into_iter()
-        .enumerate()
-        .map(|(i, s)| {
-            if modify_indices.contains(&i) {
-                let choice = rng.gen_range(0..100);
-                match choice {
-                    0..=50 => {
-                        // More often add common symbols
-                        let random_symbol = common_symbols.choose(&mut rng).unwrap();
-                        format!("{}{}", s, random_symbol)
-                    }
-                    51..=80 => {
-                        // Less often add less common symbols
-                        let random_symbol = less_common_symbols.choose(&mut rng).unwrap();
-                        format!("{}{}", s, random_symbol)
-                    }
-                    81..=99 => {
-                        // Occasionally surround with ()
-                        let surrounding = surrounding_symbols.choose(&mut rng).unwrap();
-                        let (left, right) = surrounding.split_at(1);
-                        format!("{}{}{}", left, s, right)
-                    }
-                    _ => s,
-                }
+/// Points of a chart dataset, as (x, y) pairs
+pub type DataPoints = Vec<(f64, f64)>;
+
+/// How many seconds the rolling WPM is averaged over
+pub const ROLLING_WINDOW_SECS: f64 = 5.0;
+
+/// The WPM over the course of a test, sampled every second
+pub struct WpmSeries {
+    /// From the correctly typed characters since the start
+    pub net: DataPoints,
+    /// From all typed characters since the start, including errors
+    pub raw: DataPoints,
+    /// From the correctly typed characters in the last few seconds
+    pub rolling: DataPoints,
+    /// When the errors were made, in seconds
+    pub errors: Vec<f64>,
+}
+
+pub fn wpm_series(events: &[TypingEvent]) -> WpmSeries {
+    let secs: Vec<f64> = events
+        .iter()
+        .map(|event| event.duration_since_start.as_secs_f64())
+        .collect();
+    let end = secs.last().copied().unwrap_or(0.0);
+    let mut samples: Vec<f64> = (1..=end.ceil() as u32).map(f64::from).collect();
+    if let Some(last) = samples.last_mut() {
+        *last = end;
+    }
+
+    let wpm = |chars: usize, secs: f64| chars as f64 / 5.0 / (secs / 60.0);
+    let mut series = WpmSeries {
+        net: vec![],
+        raw: vec![],
+        rolling: vec![],
+        errors: events
+            .iter()
+            .zip(&secs)
+            .filter(|(event, _)| event.error)
+            .map(|(_, secs)| *secs)
+            .collect(),
+    };
+    for time in samples {
+        let typed = || {
+            events
+                .iter()
+                .zip(&secs)
+                .filter(move |(_, secs)| **secs <= time)
+        };
+        let correct = typed().filter(|(event, _)| !event.error).count();
+        let recent = typed()
+            .filter(|(event, secs)| !event.error && **secs > time - ROLLING_WINDOW_SECS)
+            .count();
+        series.net.push((time, wpm(correct, time)));
+        series.raw.push((time, wpm(typed().count(), time)));
+        series
+            .rolling
+            .push((time, wpm(recent, time.min(ROLLING_WINDOW_SECS))));
+    }
+
+    // Start the lines at 0 seconds, where there is no WPM yet
+    for points in [&mut series.net, &mut series.raw, &mut series.rolling] {
+        if let Some(&(_, first)) = points.first() {
+            points.insert(0, (0.0, first));
+        }
+    }
+    series
+}
+
+/// The WPM of the points at the given time, interpolated between them
+pub fn wpm_at(points: &DataPoints, secs: f64) -> f64 {
+    match points.iter().position(|(time, _)| *time >= secs) {
+        Some(0) => points[0].1,
+        Some(idx) => {
+            let (t0, w0) = points[idx - 1];
+            let (t1, w1) = points[idx];
+            w0 + (w1 - w0) * (secs - t0) / (t1 - t0)
+        }
+        None => points.last().map_or(0.0, |(_, wpm)| *wpm),
+    }
+}
+
+const BASE64_ALPHABET: &[u8; 64] =
+    b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+
+/// Encodes bytes as padded base64, as the OSC 52 clipboard sequence expects
+pub fn base64(bytes: &[u8]) -> String {
+    let mut encoded = String::with_capacity(bytes.len().div_ceil(3) * 4);
+    for chunk in bytes.chunks(3) {
+        let group = chunk.iter().enumerate().fold(0u32, |group, (idx, byte)| {
+            group | (*byte as u32) << (16 - 8 * idx)
+        });
+        for idx in 0..4 {
+            if idx <= chunk.len() {
+                encoded.push(BASE64_ALPHABET[(group >> (18 - 6 * idx) & 0x3f) as usize] as char);
             } else {
-                s
+                encoded.push('=');
             }
+        }
+    }
+    encoded
+}
+
+/// How many times the time between two keystrokes reached `min_gap`
+pub fn long_gaps(events: &[TypingEvent], min_gap: Duration) -> usize {
+    events
+        .windows(2)
+        .filter(|pair| {
+            pair[1]
+                .duration_since_start
+                .saturating_sub(pair[0].duration_since_start)
+                >= min_gap
         })
-        .collect()
+        .count()
 }
 
-pub fn calculate_wpm_and_errors_datasets(
-    events: &[TypingEvent],
-) -> (Vec<(f64, f64)>, Vec<(f64, f64)>) {
-    let mut wpm_data = Vec::new();
-    let mut error_data = Vec::new();
-    let mut total_chars = 0;
+/// How many of the last seconds the live stats of the typing screen cover
+pub const LIVE_WINDOW_SECS: u64 = 20;
 
-    for (i, event) in events.iter().enumerate() {
-        let secs = event.duration_since_start.as_secs_f64();
-        let minutes = secs / 60.0;
+/// The speed and accuracy of the last seconds of a test
+pub struct RecentStats {
+    /// The WPM of each whole second, oldest first
+    pub wpm: Vec<u64>,
+    pub accuracy: f64,
+}
 
+/// The WPM of each of the last whole seconds before `elapsed`, and the
+/// accuracy of the keystrokes since the first of them
+pub fn recent_stats(events: &[TypingEvent], elapsed: Duration) -> RecentStats {
+    let now = elapsed.as_secs();
+    let first = now.saturating_sub(LIVE_WINDOW_SECS);
+    let mut chars = vec![0; (now - first) as usize];
+    let (mut correct, mut total) = (0, 0);
+    for event in events
+        .iter()
+        .filter(|event| event.duration_since_start.as_secs() >= first)
+    {
+        total += 1;
         if !event.error {
-            total_chars += 1;
-        } else {
-            error_data.push((secs, 5.0));
+            correct += 1;
+            let second = (event.duration_since_start.as_secs() - first) as usize;
+            if let Some(count) = chars.get_mut(second) {
+                *count += 1;
+            }
         }
+    }
 
-        let wpm = (total_chars as f64) / 5.0 / minutes;
-        // also push a zero time value with the wpm of the first event
-        // to make the graph start at 0
-        if i == 0 {
-            wpm_data.push((0.0, wpm));
-        }
-        wpm_data.push((secs, wpm));
+    RecentStats {
+        wpm: chars.into_iter().map(|chars| chars * 60 / 5).collect(),
+        accuracy: if total == 0 {
+            100.0
+        } else {
+            correct as f64 / total as f64 * 100.0
+        },
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn words(count: usize) -> Vec<String> {
+        vec!["word".to_string(); count]
     }
 
-    (wpm_data, error_data)
+    /// `count` keys typed 200 ms apart, every fifth one wrong
+    fn typed_events(count: u64) -> Vec<TypingEvent> {
+        (0..count)
+            .map(|idx| TypingEvent {
+                duration_since_start: Duration::from_millis(idx * 200),
+                expected: 'a',
+                typed: if idx % 5 == 4 { 'b' } else { 'a' },
+                error: idx % 5 == 4,
+                word: None,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn base64_matches_the_rfc_4648_vectors() {
+        assert_eq!(base64(b""), "");
+        assert_eq!(base64(b"f"), "Zg==");
+        assert_eq!(base64(b"fo"), "Zm8=");
+        assert_eq!(base64(b"foo"), "Zm9v");
+        assert_eq!(base64(b"foob"), "Zm9vYg==");
+        assert_eq!(base64(b"fooba"), "Zm9vYmE=");
+        assert_eq!(base64(b"foobar"), "Zm9vYmFy");
+        assert_eq!(base64("▁█".as_bytes()), "4paB4paI");
+    }
+
+    #[test]
+    fn frequency_counts_round_to_nearest_word() {
+        assert_eq!(Frequency(20).count_of(100), 20);
+        assert_eq!(Frequency(10).count_of(30), 3);
+        assert_eq!(Frequency(10).count_of(14), 1);
+        assert_eq!(Frequency(10).count_of(15), 2);
+        assert_eq!(Frequency::OFF.count_of(500), 0);
+    }
+
+    #[test]
+    fn capitalize_matches_frequency() {
+        let result = capitalize(words(100), Frequency(20));
+        let capitalized = result.iter().filter(|w| *w == "Word").count();
+        let full_capitalized = result.iter().filter(|w| *w == "WORD").count();
+        assert_eq!(capitalized + full_capitalized, 20);
+        assert_eq!(full_capitalized, 4);
+    }
+
+    #[test]
+    fn convert_to_numbers_matches_frequency() {
+        let result = convert_to_numbers(words(200), Frequency(10));
+        let numbers = result.iter().filter(|w| w.parse::<u32>().is_ok()).count();
+        assert_eq!(numbers, 20);
+    }
+
+    #[test]
+    fn add_punctuation_matches_frequency() {
+        let result = add_punctuation(words(50), Frequency(30));
+        let punctuated = result.iter().filter(|w| *w != "word").count();
+        assert_eq!(punctuated, 15);
+        assert!(result.iter().all(|w| w.starts_with("word")));
+    }
+
+    #[test]
+    fn add_brackets_matches_frequency() {
+        let result = add_brackets(words(100), Frequency(50));
+        let bracketed = result.iter().filter(|w| *w != "word").count();
+        assert_eq!(bracketed, 50);
+        assert!(result.iter().all(|w| w.contains("word")));
+    }
+
+    #[test]
+    fn off_leaves_words_untouched() {
+        let result = add_brackets(
+            add_punctuation(
+                convert_to_numbers(capitalize(words(30), Frequency::OFF), Frequency::OFF),
+                Frequency::OFF,
+            ),
+            Frequency::OFF,
+        );
+        assert_eq!(result, words(30));
+    }
+
+    #[test]
+    fn prepared_text_is_trimmed() {
+        let text = "  fn main() {\r\n\tprintln!();  \r\n}\n\n";
+        assert_eq!(prepare_text(text), "fn main() {\n    println!();\n}");
+    }
+
+    #[test]
+    fn wpm_series_is_sampled_every_second() {
+        let series = wpm_series(&typed_events(50));
+
+        let times: Vec<f64> = series.net.iter().map(|(time, _)| *time).collect();
+        assert_eq!(
+            times,
+            vec![0.0, 1.0, 2.0, 3.0, 4.0, 5.0, 6.0, 7.0, 8.0, 9.0, 9.8]
+        );
+        assert_eq!(series.errors.len(), 10);
+        // 4 correct keys a second are 48 WPM, 5 keys are 60
+        assert_eq!(
+            series.rolling.last().map(|(_, wpm)| wpm.round()),
+            Some(48.0)
+        );
+        assert_eq!(series.raw.last().map(|(_, wpm)| wpm.round()), Some(61.0));
+        assert_eq!(wpm_at(&series.net, 0.5), series.net[0].1);
+    }
+
+    #[test]
+    fn recent_stats_cover_the_last_seconds() {
+        // 10 s of typing
+        let events = typed_events(50);
+
+        // Seconds 5 to 24, with keys in the first 5 of them. 4 correct keys a
+        // second are 48 WPM.
+        let recent = recent_stats(&events, Duration::from_millis(25_500));
+        assert_eq!(recent.wpm.len(), LIVE_WINDOW_SECS as usize);
+        assert_eq!(recent.wpm[..6], [48, 48, 48, 48, 48, 0]);
+        assert_eq!(recent.accuracy, 80.0);
+
+        // Early on, the window starts with the test and the current second
+        // is not in it yet
+        let early = recent_stats(&events[..13], Duration::from_millis(2_500));
+        assert_eq!(early.wpm, vec![48, 48]);
+        assert!(recent_stats(&events[..3], Duration::from_millis(500))
+            .wpm
+            .is_empty());
+
+        // No keys in the window
+        let idle = recent_stats(&events, Duration::from_secs(60));
+        assert_eq!(idle.wpm, vec![0; LIVE_WINDOW_SECS as usize]);
+        assert_eq!(idle.accuracy, 100.0);
+        assert_eq!(recent_stats(&[], Duration::ZERO).accuracy, 100.0);
+    }
 }
@@ -0,0 +1,160 @@
+use crate::text_generator::{CharState, Character, Word};
+use crate::TypingEvent;
+use rand::seq::SliceRandom;
+use rand::thread_rng;
+
+/// At most this many of the slowest words are highlighted, and no more than a
+/// quarter of the words
+const SLOWEST_WORDS: usize = 5;
+/// How many times in a row each word is typed in a drill
+const DRILL_REPETITIONS: usize = 3;
+
+/// How a word of the text was typed
+#[derive(Debug, Clone, PartialEq)]
+pub struct WordResult {
+    pub text: String,
+    /// None for a word typed in no time, which only the first key can be
+    pub wpm: Option<f64>,
+    pub errors: usize,
+    /// Whether the word ended up typed correctly
+    pub correct: bool,
+    /// Whether the word is one of the slowest of the test
+    pub slow: bool,
+}
+
+impl WordResult {
+    /// Slow words and words with errors are worth drilling
+    pub fn needs_drill(&self) -> bool {
+        self.slow || self.errors > 0 || !self.correct
+    }
+}
+
+/// Breaks a test down by word, for the words that were typed. A word's time
+/// starts with the key before it, usually the space, or with its first key at
+/// the start of the test.
+pub fn word_results(
+    lines: &[Vec<Character>],
+    words: &[Word],
+    events: &[TypingEvent],
+) -> Vec<WordResult> {
+    let mut results: Vec<WordResult> = words
+        .iter()
+        .enumerate()
+        .filter_map(|(idx, word)| {
+            let first = events.iter().position(|e| e.word == Some(idx))?;
+            let last = events.iter().rposition(|e| e.word == Some(idx))?;
+            let start = events[first.saturating_sub(1)].duration_since_start;
+            let secs = events[last]
+                .duration_since_start
+                .saturating_sub(start)
+                .as_secs_f64();
+            let chars = &lines[word.line][word.start..word.end];
+            Some(WordResult {
+                text: chars.iter().map(|c| c.c).collect(),
+                wpm: (secs > 0.0).then(|| chars.len() as f64 / 5.0 / (secs / 60.0)),
+                errors: events[first..=last]
+                    .iter()
+                    .filter(|e| e.word == Some(idx) && e.error)
+                    .count(),
+                correct: chars.iter().all(|c| c.state == CharState::Correct),
+                slow: false,
+            })
+        })
+        .collect();
+
+    let mut by_speed: Vec<usize> = (0..results.len())
+        .filter(|idx| results[*idx].wpm.is_some())
+        .collect();
+    by_speed.sort_by(|a, b| {
+        let wpm = |idx: &usize| results[*idx].wpm.unwrap_or_default();
+        wpm(a).total_cmp(&wpm(b))
+    });
+    for idx in by_speed
+        .into_iter()
+        .take(SLOWEST_WORDS.min(results.len() / 4))
+    {
+        results[idx].slow = true;
+    }
+    results
+}
+
+/// The words worth drilling, each once
+pub fn drill_words(results: &[WordResult]) -> Vec<String> {
+    let mut words: Vec<String> = vec![];
+    for result in results.iter().filter(|r| r.needs_drill()) {
+        if !words.contains(&result.text) {
+            words.push(result.text.clone());
+        }
+    }
+    words
+}
+
+/// Each of the words repeated a few times in a row, in random order
+pub fn drill_text(words: &[String]) -> Vec<String> {
+    let mut words = words.to_vec();
+    words.shuffle(&mut thread_rng());
+    words
+        .into_iter()
+        .flat_map(|word| std::iter::repeat_n(word, DRILL_REPETITIONS))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::text_generator::find_words;
+    use std::time::Duration;
+
+    fn event(ms: u64, typed: char, expected: char, word: Option<usize>) -> TypingEvent {
+        TypingEvent {
+            duration_since_start: Duration::from_millis(ms),
+            expected,
+            typed,
+            error: typed != expected,
+            word,
+        }
+    }
+
+    #[test]
+    fn words_are_timed_from_the_key_before() {
+        let mut lines = vec!["ab cd".chars().map(Character::new).collect::<Vec<_>>()];
+        let words = find_words(&lines);
+        assert_eq!(words.len(), 2);
+        for (idx, c) in "ab cx".chars().enumerate() {
+            lines[0][idx].set_typed(c);
+        }
+        let events = vec![
+            event(0, 'a', 'a', Some(0)),
+            event(100, 'b', 'b', Some(0)),
+            event(200, ' ', ' ', None),
+            event(800, 'c', 'c', Some(1)),
+            event(1400, 'x', 'd', Some(1)),
+        ];
+
+        let results = word_results(&lines, &words, &events);
+        // 2 characters in 100 ms and in 1.2 s
+        assert_eq!(results[0].wpm.map(f64::round), Some(240.0));
+        assert_eq!(results[1].wpm.map(f64::round), Some(20.0));
+        assert_eq!((results[1].errors, results[1].correct), (1, false));
+        assert_eq!(drill_words(&results), vec!["cd".to_string()]);
+        assert_eq!(drill_text(&drill_words(&results)).len(), 3);
+    }
+
+    #[test]
+    fn the_first_word_is_timed_from_its_first_key() {
+        let lines = vec!["a bc".chars().map(Character::new).collect::<Vec<_>>()];
+        let words = find_words(&lines);
+        // The first key is recorded a moment after the timer starts
+        let events = vec![
+            event(3, 'a', 'a', Some(0)),
+            event(203, ' ', ' ', None),
+            event(403, 'b', 'b', Some(1)),
+            event(603, 'c', 'c', Some(1)),
+        ];
+
+        let results = word_results(&lines, &words, &events);
+        assert_eq!(results[0].wpm, None);
+        // 2 characters in 400 ms
+        assert_eq!(results[1].wpm.map(f64::round), Some(60.0));
+    }
+}